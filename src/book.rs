@@ -0,0 +1,255 @@
+use crate::{UAPlayerError, format, is_audiofile};
+use std::io::Write;
+
+#[derive(Debug, Clone)]
+pub struct Book {
+    pub path: String,
+    pub parts: Vec<BookPart>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BookPart {
+    pub path: String,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BookPosition {
+    pub time: f64,
+    pub part: Option<(String, f64)>,
+}
+
+impl BookPart {
+    pub fn file_name(&self) -> String {
+        std::path::Path::new(&self.path)
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.clone())
+    }
+
+    pub fn display_title(&self) -> String {
+        self.title.clone().unwrap_or_else(|| {
+            std::path::Path::new(&self.path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| self.path.clone())
+        })
+    }
+}
+
+impl Book {
+    pub fn open(path: &std::path::Path) -> Result<Self, UAPlayerError> {
+        let abs_path = std::path::absolute(path)?;
+        if abs_path.is_dir() {
+            return Book::open_dir(&abs_path);
+        }
+        if is_playlist(&abs_path) {
+            return Book::open_playlist(&abs_path);
+        }
+        format::detect(&abs_path).map_err(UAPlayerError::InvalidFile)?;
+
+        let path = abs_path.to_string_lossy().to_string();
+        Ok(Book {
+            path: path.clone(),
+            parts: vec![BookPart { path, title: None }],
+        })
+    }
+
+    fn open_dir(path: &std::path::Path) -> Result<Self, UAPlayerError> {
+        let mut files: Vec<std::path::PathBuf> = std::fs::read_dir(path)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.is_file() && is_audiofile(p))
+            .collect();
+        if files.is_empty() {
+            return Err(UAPlayerError::InvalidFile(format!(
+                "{} contains no audio files",
+                path.to_string_lossy()
+            )));
+        }
+        files.sort_by(|a, b| natural_cmp(&a.to_string_lossy(), &b.to_string_lossy()));
+
+        Ok(Book {
+            path: path.to_string_lossy().to_string(),
+            parts: files
+                .into_iter()
+                .map(|p| BookPart {
+                    path: p.to_string_lossy().to_string(),
+                    title: None,
+                })
+                .collect(),
+        })
+    }
+
+    fn open_playlist(path: &std::path::Path) -> Result<Self, UAPlayerError> {
+        let name = path.to_string_lossy().to_string();
+        let content = std::fs::read(path)
+            .map_err(|err| UAPlayerError::InvalidFile(format!("cannot read {name}: {err}")))?;
+        let content = String::from_utf8_lossy(&content);
+        let dir = path.parent().unwrap_or(std::path::Path::new("/"));
+
+        let mut parts = vec![];
+        let mut title = None;
+        for line in content.trim_start_matches('\u{feff}').lines() {
+            let line = line.trim();
+            if let Some(info) = line.strip_prefix("#EXTINF:") {
+                title = info
+                    .split_once(',')
+                    .map(|(_, t)| t.trim().to_string())
+                    .filter(|t| !t.is_empty());
+                continue;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let entry = line.strip_prefix("file://").unwrap_or(line);
+            if entry.contains("://") {
+                log::error!("Book: skipping remote playlist entry {entry}");
+                title = None;
+                continue;
+            }
+            let entry_path = dir.join(entry);
+            format::detect(&entry_path)
+                .map_err(|reason| UAPlayerError::InvalidFile(format!("{name}: {reason}")))?;
+            parts.push(BookPart {
+                path: entry_path.to_string_lossy().to_string(),
+                title: title.take(),
+            });
+        }
+        if parts.is_empty() {
+            return Err(UAPlayerError::InvalidFile(format!(
+                "{name} contains no playable entries"
+            )));
+        }
+
+        Ok(Book { path: name, parts })
+    }
+
+    pub fn is_multipart(&self) -> bool {
+        self.parts.len() > 1
+    }
+
+    pub fn title(&self) -> String {
+        let path = std::path::Path::new(&self.path);
+        let title = if path.is_dir() {
+            path.file_name()
+        } else {
+            path.file_stem()
+        };
+
+        title
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.clone())
+    }
+
+    // Parts are told apart by their path relative to the book, as file names can repeat
+    // across subdirectories of a playlist.
+    pub fn part_name(&self, i: usize) -> String {
+        let path = std::path::Path::new(&self.path);
+        let base = if path.is_dir() {
+            path
+        } else {
+            path.parent().unwrap_or(path)
+        };
+        let part = std::path::Path::new(&self.parts[i].path);
+
+        part.strip_prefix(base)
+            .unwrap_or(part)
+            .to_string_lossy()
+            .to_string()
+    }
+
+    pub fn mpv_path(&self) -> String {
+        if !self.is_multipart() {
+            return self.parts[0].path.clone();
+        }
+
+        // Segments are titled with the file name, read_chapters finds the parts by it.
+        self.edl_path(BookPart::file_name)
+    }
+
+    // Every part becomes an EDL segment whose title mpv turns into the part's chapter.
+    // Lengths are given in bytes so paths and titles can contain EDL separators.
+    pub fn edl_path(&self, title: impl Fn(&BookPart) -> String) -> String {
+        let segments: Vec<String> = self
+            .parts
+            .iter()
+            .map(|p| {
+                let title = title(p);
+                format!(
+                    "%{}%{},title=%{}%{title}",
+                    p.path.len(),
+                    p.path,
+                    title.len()
+                )
+            })
+            .collect();
+        format!("edl://{}", segments.join(";"))
+    }
+
+    pub fn load_sidecar(&self) -> Option<BookPosition> {
+        let str = std::fs::read_to_string(format!("{}.txt", self.path)).ok()?;
+
+        let mut lines = str.lines();
+        let time = lines.next()?.trim().parse().ok()?;
+        let part = match (lines.next(), lines.next()) {
+            (Some(name), Some(offset)) => offset.trim().parse().ok().map(|o| (name.to_string(), o)),
+            _ => None,
+        };
+
+        Some(BookPosition { time, part })
+    }
+
+    pub fn save_sidecar(&self, position: &BookPosition) -> Result<(), std::io::Error> {
+        let mut file = std::fs::File::create(format!("{}.txt", self.path))?;
+        file.write_all(position.time.to_string().as_bytes())?;
+        if let Some((name, offset)) = &position.part {
+            write!(file, "\n{name}\n{offset}")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn is_playlist(path: &std::path::Path) -> bool {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|e| e == "m3u" || e == "m3u8")
+}
+
+fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+
+    loop {
+        match (a.peek(), b.peek()) {
+            (None, None) => return std::cmp::Ordering::Equal,
+            (None, Some(_)) => return std::cmp::Ordering::Less,
+            (Some(_), None) => return std::cmp::Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let mut x_num = String::new();
+                while let Some(c) = a.next_if(|c| c.is_ascii_digit()) {
+                    x_num.push(c);
+                }
+                let mut y_num = String::new();
+                while let Some(c) = b.next_if(|c| c.is_ascii_digit()) {
+                    y_num.push(c);
+                }
+                let x_num = x_num.trim_start_matches('0');
+                let y_num = y_num.trim_start_matches('0');
+                let ord = x_num.len().cmp(&y_num.len()).then(x_num.cmp(y_num));
+                if ord != std::cmp::Ordering::Equal {
+                    return ord;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ord = x.cmp(y);
+                if ord != std::cmp::Ordering::Equal {
+                    return ord;
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
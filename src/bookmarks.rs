@@ -0,0 +1,24 @@
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct Bookmark {
+    pub time: f64,
+    pub chapter: Option<String>,
+    pub note: String,
+    pub created: i64,
+}
+
+impl Bookmark {
+    pub fn new(time: f64, chapter: Option<String>, note: String) -> Self {
+        Bookmark {
+            time,
+            chapter,
+            note,
+            created: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+// Bookmarks are kept ordered by their position in the book.
+pub fn insert_bookmark(bookmarks: &mut Vec<Bookmark>, bookmark: Bookmark) {
+    let i = bookmarks.partition_point(|b| b.time <= bookmark.time);
+    bookmarks.insert(i, bookmark);
+}
@@ -0,0 +1,144 @@
+use crate::UAPlayerError;
+use crate::config::{FallbackChaptersConfig, state_dir};
+use crate::libmpv_handler::Chapter;
+
+const SILENCE_MIN_CHAPTER_LEN: f64 = 60.0;
+
+pub fn interval_chapters(duration: f64, interval: f64) -> Vec<Chapter> {
+    if duration <= 0.0 || interval <= 0.0 {
+        return vec![];
+    }
+
+    (0..)
+        .map(|i| i as f64 * interval)
+        .take_while(|&time| time < duration)
+        .enumerate()
+        .map(|(i, time)| Chapter {
+            title: format!("Chapter {:02}", i + 1),
+            time: time as f32,
+        })
+        .collect()
+}
+
+pub fn detect_silence_chapters(
+    mpv_path: &str,
+    config: &FallbackChaptersConfig,
+) -> Result<Vec<Chapter>, libmpv2::Error> {
+    let mut mpv = libmpv2::Mpv::with_initializer(|init| {
+        init.set_property("vo", "null")?;
+        init.set_property("ao", "null")?;
+        init.set_property("ao-null-untimed", true)?;
+        init.set_property(
+            "af",
+            format!(
+                "@silence:lavfi=[silencedetect=n={}dB:d={}]",
+                config.silence_noise, config.silence_duration
+            ),
+        )?;
+        Ok(())
+    })?;
+    mpv.disable_deprecated_events()?;
+    mpv.observe_property("af-metadata/silence", libmpv2::Format::String, 0)?;
+    mpv.command("loadfile", &[mpv_path, "replace"])?;
+
+    let mut chapter_starts = vec![0.0];
+    loop {
+        match mpv.wait_event(60.0) {
+            Some(Ok(libmpv2::events::Event::PropertyChange {
+                name: "af-metadata/silence",
+                change: libmpv2::events::PropertyData::Str(data),
+                ..
+            })) => {
+                let silence_end =
+                    serde_json::from_str::<std::collections::HashMap<String, String>>(data)
+                        .ok()
+                        .and_then(|m| {
+                            m.get("lavfi.silence_end")
+                                .and_then(|s| s.parse::<f64>().ok())
+                        });
+                let last_start = *chapter_starts.last().unwrap();
+                if let Some(time) =
+                    silence_end.filter(|t| t - last_start >= SILENCE_MIN_CHAPTER_LEN)
+                {
+                    chapter_starts.push(time);
+                }
+            }
+            Some(Ok(libmpv2::events::Event::EndFile(libmpv2::mpv_end_file_reason::Eof))) => {
+                break;
+            }
+            // A scan cut short must not be cached as the book's chapters.
+            Some(Ok(libmpv2::events::Event::EndFile(_)))
+            | Some(Ok(libmpv2::events::Event::Shutdown))
+            | None => return Err(libmpv2::Error::Null),
+            Some(Err(err)) => return Err(err),
+            Some(Ok(_)) => (),
+        }
+    }
+
+    Ok(chapter_starts
+        .into_iter()
+        .enumerate()
+        .map(|(i, time)| Chapter {
+            title: format!("Chapter {:02}", i + 1),
+            time: time as f32,
+        })
+        .collect())
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+struct CachedChapters {
+    silence_noise: f64,
+    silence_duration: f64,
+    chapters: Vec<Chapter>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default)]
+pub struct ChaptersCache {
+    books: std::collections::BTreeMap<String, CachedChapters>,
+}
+
+impl ChaptersCache {
+    fn file_path() -> Option<std::path::PathBuf> {
+        state_dir().map(|dir| dir.join("chapters.json"))
+    }
+
+    pub fn load() -> Self {
+        ChaptersCache::file_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|str| serde_json::from_str(&str).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), UAPlayerError> {
+        let Some(path) = ChaptersCache::file_path() else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self).unwrap())?;
+
+        Ok(())
+    }
+
+    pub fn get(&self, path: &str, config: &FallbackChaptersConfig) -> Option<Vec<Chapter>> {
+        self.books
+            .get(path)
+            .filter(|c| {
+                c.silence_noise == config.silence_noise
+                    && c.silence_duration == config.silence_duration
+            })
+            .map(|c| c.chapters.clone())
+    }
+
+    pub fn insert(&mut self, path: &str, config: &FallbackChaptersConfig, chapters: Vec<Chapter>) {
+        self.books.insert(
+            path.to_string(),
+            CachedChapters {
+                silence_noise: config.silence_noise,
+                silence_duration: config.silence_duration,
+                chapters,
+            },
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use crate::UAPlayerError;
+use crate::eq::FLAT_PRESET;
+use crate::timer::TimerClock;
+
+#[derive(serde::Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Config {
+    pub library: Vec<String>,
+    pub write_sidecars: bool,
+    pub fallback_chapters: FallbackChaptersConfig,
+    pub skip_silence: SkipSilenceConfig,
+    pub normalization: NormalizationConfig,
+    pub eq: EqConfig,
+    pub audio: AudioConfig,
+    pub smart_rewind: SmartRewindConfig,
+    pub sleep_timer: SleepTimerConfig,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SleepTimerConfig {
+    pub clock: TimerClock,
+    pub fade: f64,
+    pub still_listening: bool,
+}
+
+impl Default for SleepTimerConfig {
+    fn default() -> Self {
+        SleepTimerConfig {
+            clock: TimerClock::Wall,
+            fade: 30.0,
+            still_listening: false,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct RewindTier {
+    pub after: f64,
+    pub rewind: f64,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SmartRewindConfig {
+    pub tiers: Vec<RewindTier>,
+}
+
+impl Default for SmartRewindConfig {
+    fn default() -> Self {
+        SmartRewindConfig {
+            tiers: [(60.0, 2.0), (600.0, 5.0), (3600.0, 15.0), (86400.0, 30.0)]
+                .into_iter()
+                .map(|(after, rewind)| RewindTier { after, rewind })
+                .collect(),
+        }
+    }
+}
+
+impl SmartRewindConfig {
+    pub fn rewind_after(&self, paused: f64) -> f64 {
+        self.tiers
+            .iter()
+            .filter(|t| t.after <= paused)
+            .map(|t| t.rewind)
+            .fold(0.0, f64::max)
+    }
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SkipSilenceConfig {
+    pub enabled: bool,
+    pub threshold: f64,
+    pub min_gap: f64,
+}
+
+impl Default for SkipSilenceConfig {
+    fn default() -> Self {
+        SkipSilenceConfig {
+            enabled: false,
+            threshold: -50.0,
+            min_gap: 0.5,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct AudioConfig {
+    pub device: Option<String>,
+    pub mono: bool,
+    pub balance: f64,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct EqBand {
+    pub frequency: f64,
+    pub gain: f64,
+    pub width: f64,
+}
+
+impl Default for EqBand {
+    fn default() -> Self {
+        EqBand {
+            frequency: 1000.0,
+            gain: 0.0,
+            width: 1.0,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct EqConfig {
+    pub default: String,
+    pub presets: std::collections::HashMap<String, Vec<EqBand>>,
+}
+
+impl Default for EqConfig {
+    fn default() -> Self {
+        EqConfig {
+            default: FLAT_PRESET.to_string(),
+            presets: std::collections::HashMap::new(),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum NormalizationMode {
+    Off,
+    Loudnorm,
+    Dynaudnorm,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct NormalizationConfig {
+    pub mode: NormalizationMode,
+    pub target: f64,
+    pub compressor: bool,
+    pub compressor_threshold: f64,
+    pub compressor_ratio: f64,
+}
+
+impl Default for NormalizationConfig {
+    fn default() -> Self {
+        NormalizationConfig {
+            mode: NormalizationMode::Off,
+            target: -16.0,
+            compressor: false,
+            compressor_threshold: -24.0,
+            compressor_ratio: 4.0,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FallbackChaptersMode {
+    None,
+    Interval,
+    Silence,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct FallbackChaptersConfig {
+    pub mode: FallbackChaptersMode,
+    pub interval: f64,
+    pub silence_noise: f64,
+    pub silence_duration: f64,
+}
+
+impl Default for FallbackChaptersConfig {
+    fn default() -> Self {
+        FallbackChaptersConfig {
+            mode: FallbackChaptersMode::Interval,
+            interval: 600.0,
+            silence_noise: -35.0,
+            silence_duration: 2.0,
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Self, UAPlayerError> {
+        let Some(path) = config_dir().map(|dir| dir.join("config.json")) else {
+            return Ok(Config::default());
+        };
+        if !path.is_file() {
+            return Ok(Config::default());
+        }
+
+        let str = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&str).map_err(|err| {
+            UAPlayerError::InvalidConfig(format!("{}: {err}", path.to_string_lossy()))
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn config_dir() -> Option<std::path::PathBuf> {
+    std::env::var("XDG_CONFIG_HOME")
+        .or(std::env::var("HOME").map(|s| format!("{s}/.config")))
+        .map(|path| std::path::PathBuf::from(format!("{path}/{}", env!("CARGO_PKG_NAME"))))
+        .ok()
+}
+
+#[cfg(target_os = "windows")]
+pub fn config_dir() -> Option<std::path::PathBuf> {
+    std::env::var("APPDATA")
+        .map(|path| std::path::PathBuf::from(format!("{path}/{}", env!("CARGO_PKG_NAME"))))
+        .ok()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn config_dir() -> Option<std::path::PathBuf> {
+    Some(std::path::PathBuf::from("."))
+}
+
+#[cfg(target_os = "linux")]
+pub fn state_dir() -> Option<std::path::PathBuf> {
+    std::env::var("XDG_STATE_HOME")
+        .or(std::env::var("HOME").map(|s| format!("{s}/.local/state")))
+        .map(|path| std::path::PathBuf::from(format!("{path}/{}", env!("CARGO_PKG_NAME"))))
+        .ok()
+}
+
+#[cfg(target_os = "windows")]
+pub fn state_dir() -> Option<std::path::PathBuf> {
+    config_dir()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn state_dir() -> Option<std::path::PathBuf> {
+    Some(std::path::PathBuf::from("."))
+}
@@ -0,0 +1,151 @@
+use crate::libmpv_handler::Chapter;
+
+pub fn find_cue(path: &std::path::Path) -> Option<std::path::PathBuf> {
+    let candidates = [
+        path.with_extension("cue"),
+        std::path::PathBuf::from(format!("{}.cue", path.to_string_lossy())),
+    ];
+
+    candidates.into_iter().find(|p| p.is_file())
+}
+
+pub fn load_cue_chapters(path: &std::path::Path) -> Option<Vec<Chapter>> {
+    let cue_path = find_cue(path)?;
+    let content = std::fs::read(&cue_path).ok()?;
+    let file_name = path.file_name()?.to_string_lossy().to_string();
+
+    let chapters = parse_cue(&String::from_utf8_lossy(&content), &file_name);
+    if chapters.is_empty() {
+        None
+    } else {
+        Some(chapters)
+    }
+}
+
+pub fn parse_cue(content: &str, file_name: &str) -> Vec<Chapter> {
+    let files_count = content
+        .lines()
+        .filter(|l| l.trim_start().starts_with("FILE "))
+        .count();
+
+    let mut chapters = vec![];
+    // With several FILE entries only the tracks of this file are used.
+    let mut in_file = files_count <= 1;
+    let mut track: Option<(usize, Option<String>)> = None;
+    for line in content.trim_start_matches('\u{feff}').lines() {
+        let line = line.trim();
+        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match keyword {
+            "FILE" if files_count > 1 => {
+                in_file = unquote(rest.rsplit_once(' ').map_or(rest, |(f, _)| f)) == file_name;
+                track = None;
+            }
+            "TRACK" if in_file => {
+                let num = rest.split_whitespace().next().and_then(|n| n.parse().ok());
+                track = Some((num.unwrap_or(chapters.len() + 1), None));
+            }
+            "TITLE" => {
+                if let Some((_, ref mut title)) = track {
+                    *title = Some(unquote(rest).to_string());
+                }
+            }
+            "INDEX" => {
+                let mut args = rest.split_whitespace();
+                if args.next() != Some("01") {
+                    continue;
+                }
+                if let (Some((num, title)), Some(time)) =
+                    (track.take(), args.next().and_then(parse_cue_time))
+                {
+                    chapters.push(Chapter {
+                        title: title.unwrap_or_else(|| format!("Track {num:02}")),
+                        time: time as f32,
+                    });
+                }
+            }
+            _ => (),
+        }
+    }
+
+    chapters
+}
+
+fn unquote(str: &str) -> &str {
+    let str = str.trim();
+    str.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(str)
+}
+
+fn parse_cue_time(str: &str) -> Option<f64> {
+    let mut fields = str.split(':');
+    let mm: f64 = fields.next()?.parse().ok()?;
+    let ss: f64 = fields.next()?.parse().ok()?;
+    let ff: f64 = fields.next()?.parse().ok()?;
+
+    Some(mm * 60.0 + ss + ff / 75.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CUE: &str = "\u{feff}PERFORMER \"Author\"
+TITLE \"Book\"
+FILE \"book.m4b\" MP4
+  TRACK 01 AUDIO
+    TITLE \"Opening\"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE \"Chapter One\"
+    INDEX 00 01:59:00
+    INDEX 01 02:03:30
+  TRACK 03 AUDIO
+    INDEX 01 75:00:74
+";
+
+    #[test]
+    fn cue_time_frames() {
+        assert_eq!(parse_cue_time("00:00:00"), Some(0.0));
+        assert_eq!(parse_cue_time("02:03:30"), Some(123.4));
+        assert_eq!(parse_cue_time("02:03"), None);
+    }
+
+    #[test]
+    fn cue_index_01() {
+        let chapters = parse_cue(CUE, "book.m4b");
+        let chapters: Vec<(&str, f32)> = chapters
+            .iter()
+            .map(|c| (c.title.as_str(), c.time))
+            .collect();
+        assert_eq!(
+            chapters,
+            [
+                ("Opening", 0.0),
+                ("Chapter One", 123.4),
+                ("Track 03", (4500.0 + 74.0 / 75.0) as f32)
+            ]
+        );
+    }
+
+    #[test]
+    fn cue_several_files() {
+        let cue = "FILE \"part1.mp3\" MP3
+  TRACK 01 AUDIO
+    TITLE \"One\"
+    INDEX 01 00:00:00
+FILE \"part2.mp3\" MP3
+  TRACK 02 AUDIO
+    TITLE \"Two\"
+    INDEX 01 00:00:00
+  TRACK 03 AUDIO
+    TITLE \"Three\"
+    INDEX 01 10:00:00
+";
+        let titles: Vec<String> = parse_cue(cue, "part2.mp3")
+            .into_iter()
+            .map(|c| c.title)
+            .collect();
+        assert_eq!(titles, ["Two", "Three"]);
+    }
+}
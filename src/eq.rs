@@ -0,0 +1,65 @@
+use crate::config::{EqBand, EqConfig};
+
+pub const FLAT_PRESET: &str = "flat";
+
+// Frequency in Hz, gain in dB and width in octaves.
+type Band = (f64, f64, f64);
+
+const BUILTIN_PRESETS: [(&str, &[Band]); 4] = [
+    (FLAT_PRESET, &[]),
+    (
+        "voice-boost",
+        &[(120.0, -3.0, 1.0), (2500.0, 4.0, 1.5), (5000.0, 2.0, 1.0)],
+    ),
+    (
+        "reduce-sibilance",
+        &[(6500.0, -6.0, 0.7), (8500.0, -4.0, 1.0)],
+    ),
+    ("bass-cut", &[(60.0, -12.0, 1.0), (150.0, -6.0, 1.0)]),
+];
+
+// Presets from the config file take precedence over the built-in ones.
+pub fn preset_bands(name: &str, config: &EqConfig) -> Option<Vec<EqBand>> {
+    if let Some(bands) = config.presets.get(name) {
+        return Some(bands.clone());
+    }
+
+    BUILTIN_PRESETS
+        .iter()
+        .find(|(preset, _)| *preset == name)
+        .map(|(_, bands)| {
+            bands
+                .iter()
+                .map(|&(frequency, gain, width)| EqBand {
+                    frequency,
+                    gain,
+                    width,
+                })
+                .collect()
+        })
+}
+
+pub fn preset_names(config: &EqConfig) -> Vec<String> {
+    let mut names: Vec<String> = BUILTIN_PRESETS
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .chain(config.presets.keys().cloned())
+        .collect();
+    names.sort();
+    names.dedup();
+
+    names
+}
+
+pub fn eq_filter(bands: &[EqBand]) -> Option<String> {
+    let bands: Vec<String> = bands
+        .iter()
+        .filter(|b| b.gain != 0.0)
+        .map(|b| format!("equalizer=f={}:t=o:w={}:g={}", b.frequency, b.width, b.gain))
+        .collect();
+    if bands.is_empty() {
+        return None;
+    }
+
+    Some(format!("lavfi=[{}]", bands.join(",")))
+}
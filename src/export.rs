@@ -0,0 +1,217 @@
+use crate::UAPlayerError;
+use crate::book::{Book, BookPart};
+use crate::chapters::{detect_silence_chapters, interval_chapters};
+use crate::config::{Config, FallbackChaptersMode};
+use crate::libmpv_handler::{Chapter, open_standalone, read_chapters};
+
+pub const CLIP_DEFAULT_EXTENSION: &str = "m4a";
+const SPLIT_EXTENSION: &str = "mp3";
+const MERGE_DEFAULT_EXTENSION: &str = "m4b";
+
+#[derive(Debug, Clone)]
+pub enum ClipStatus {
+    Progress(u8),
+    Done(String),
+    Failed(String),
+}
+
+// Only encoders built into every FFmpeg are picked for the default extension.
+fn clip_encoder(path: &str) -> &'static str {
+    let extension = std::path::Path::new(path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase());
+    match extension.as_deref() {
+        Some("mp3") => "libmp3lame",
+        Some("opus") => "libopus",
+        Some("ogg") => "libvorbis",
+        Some("flac") => "flac",
+        Some("wav") => "pcm_s16le",
+        _ => "aac",
+    }
+}
+
+pub fn clip_path(path: Option<String>, title: &str, start: f64, end: f64) -> String {
+    match path {
+        Some(path) if std::path::Path::new(&path).extension().is_some() => path,
+        Some(path) => format!("{path}.{CLIP_DEFAULT_EXTENSION}"),
+        None => format!(
+            "{} {}-{}.{CLIP_DEFAULT_EXTENSION}",
+            safe_file_name(title),
+            secs_to_file_time(start),
+            secs_to_file_time(end)
+        ),
+    }
+}
+
+fn safe_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if "/\\:*?\"<>|".contains(c) { '_' } else { c })
+        .collect()
+}
+
+// Option values are length prefixed so they can contain commas and quotes.
+fn metadata_list(metadata: &[(&str, String)]) -> String {
+    metadata
+        .iter()
+        .map(|(key, value)| format!("{key}=%{}%{value}", value.len()))
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn secs_to_file_time(seconds: f64) -> String {
+    let seconds = seconds.floor() as u64;
+    format!(
+        "{:02}-{:02}-{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+pub fn export_clip(
+    mpv_path: &str,
+    start: f64,
+    end: f64,
+    output: &str,
+    status_s: &crossbeam::channel::Sender<ClipStatus>,
+) -> Result<(), libmpv2::Error> {
+    encode(mpv_path, start, end, output, &[], status_s)
+}
+
+fn encode(
+    mpv_path: &str,
+    start: f64,
+    end: f64,
+    output: &str,
+    metadata: &[(&str, String)],
+    status_s: &crossbeam::channel::Sender<ClipStatus>,
+) -> Result<(), libmpv2::Error> {
+    {
+        let mut mpv = libmpv2::Mpv::with_initializer(|init| {
+            init.set_property("o", output)?;
+            init.set_property("oac", clip_encoder(output))?;
+            init.set_property("start", start.to_string())?;
+            init.set_property("end", end.to_string())?;
+            init.set_property("vid", "no")?;
+            init.set_property("sid", "no")?;
+            if !metadata.is_empty() {
+                init.set_property("oset-metadata", metadata_list(metadata))?;
+            }
+            Ok(())
+        })?;
+        mpv.disable_deprecated_events()?;
+        mpv.observe_property("time-pos/full", libmpv2::Format::Double, 0)?;
+        mpv.command("loadfile", &[mpv_path, "replace"])?;
+
+        let mut last_percent = 0;
+        loop {
+            match mpv.wait_event(60.0) {
+                Some(Ok(libmpv2::events::Event::PropertyChange {
+                    name: "time-pos/full",
+                    change: libmpv2::events::PropertyData::Double(time),
+                    ..
+                })) => {
+                    let percent = ((time - start) / (end - start) * 100.0).clamp(0.0, 99.0) as u8;
+                    if percent != last_percent {
+                        last_percent = percent;
+                        let _ = status_s.send(ClipStatus::Progress(percent));
+                    }
+                }
+                Some(Ok(libmpv2::events::Event::EndFile(libmpv2::mpv_end_file_reason::Eof))) => {
+                    break;
+                }
+                Some(Ok(libmpv2::events::Event::EndFile(_))) | None => {
+                    return Err(libmpv2::Error::Null);
+                }
+                Some(Err(err)) => return Err(err),
+                Some(Ok(_)) => (),
+            }
+        }
+    }
+    // The output file is only finalized once the encoding handle is destroyed.
+    let _ = status_s.send(ClipStatus::Done(output.to_string()));
+
+    Ok(())
+}
+
+fn book_chapters(book: &Book, config: &Config) -> Result<(Vec<Chapter>, f64), UAPlayerError> {
+    let mpv = open_standalone(book)?;
+    let duration = mpv.get_property::<f64>("duration/full")?;
+    let mut chapters = read_chapters(&mpv, book)?.chapters;
+    drop(mpv);
+
+    if chapters.is_empty() {
+        let fallback = &config.fallback_chapters;
+        chapters = match fallback.mode {
+            FallbackChaptersMode::None => vec![],
+            FallbackChaptersMode::Interval => interval_chapters(duration, fallback.interval),
+            FallbackChaptersMode::Silence => detect_silence_chapters(&book.mpv_path(), fallback)?,
+        };
+    }
+    if chapters.is_empty() {
+        chapters.push(Chapter {
+            title: book.title(),
+            time: 0.0,
+        });
+    }
+
+    Ok((chapters, duration))
+}
+
+// Writes every chapter to its own file named after the chapter title.
+pub fn split_book(book: &Book, config: &Config, dir: &str) -> Result<Vec<String>, UAPlayerError> {
+    let (chapters, duration) = book_chapters(book, config)?;
+    std::fs::create_dir_all(dir)?;
+
+    let (status_s, _) = crossbeam::channel::unbounded();
+    let mut outputs = vec![];
+    for (i, chapter) in chapters.iter().enumerate() {
+        let start = chapter.time as f64;
+        let end = chapters.get(i + 1).map_or(duration, |c| c.time as f64);
+        let output = std::path::Path::new(dir)
+            .join(format!(
+                "{:02} - {}.{SPLIT_EXTENSION}",
+                i + 1,
+                safe_file_name(&chapter.title)
+            ))
+            .to_string_lossy()
+            .to_string();
+        let metadata = [
+            ("title", chapter.title.clone()),
+            ("album", book.title()),
+            ("track", format!("{}/{}", i + 1, chapters.len())),
+        ];
+        encode(&book.mpv_path(), start, end, &output, &metadata, &status_s)?;
+        outputs.push(output);
+    }
+
+    Ok(outputs)
+}
+
+pub fn merge_book(book: &Book, output: &str) -> Result<String, UAPlayerError> {
+    if !book.is_multipart() {
+        return Err(UAPlayerError::InvalidFile(format!(
+            "{} has a single part, there is nothing to merge",
+            book.path
+        )));
+    }
+    let output = if std::path::Path::new(output).extension().is_some() {
+        output.to_string()
+    } else {
+        format!("{output}.{MERGE_DEFAULT_EXTENSION}")
+    };
+
+    let duration = open_standalone(book)?.get_property::<f64>("duration/full")?;
+    let (status_s, _) = crossbeam::channel::unbounded();
+    let metadata = [("title", book.title()), ("album", book.title())];
+    encode(
+        &book.edl_path(BookPart::display_title),
+        0.0,
+        duration,
+        &output,
+        &metadata,
+        &status_s,
+    )?;
+
+    Ok(output)
+}
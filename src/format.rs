@@ -0,0 +1,168 @@
+use std::io::Read;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AudioFormat {
+    M4b,
+    M4a,
+    Mp3,
+    Aac,
+    Opus,
+    Vorbis,
+    Flac,
+    Mka,
+    Wav,
+}
+
+static EXTENSIONS: phf::Map<&'static str, AudioFormat> = phf::phf_map! {
+    "m4b" => AudioFormat::M4b,
+    "m4a" => AudioFormat::M4a,
+    "mp3" => AudioFormat::Mp3,
+    "aac" => AudioFormat::Aac,
+    "opus" => AudioFormat::Opus,
+    "ogg" => AudioFormat::Vorbis,
+    "oga" => AudioFormat::Vorbis,
+    "flac" => AudioFormat::Flac,
+    "mka" => AudioFormat::Mka,
+    "wav" => AudioFormat::Wav,
+};
+
+pub fn format_from_extension(path: &std::path::Path) -> Option<AudioFormat> {
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    EXTENSIONS.get(ext.as_str()).copied()
+}
+
+pub fn sniff(header: &[u8]) -> Option<AudioFormat> {
+    if header.starts_with(b"ID3") {
+        return Some(AudioFormat::Mp3);
+    }
+    if header.len() >= 12 && &header[4..8] == b"ftyp" {
+        return if &header[8..12] == b"M4B " {
+            Some(AudioFormat::M4b)
+        } else {
+            Some(AudioFormat::M4a)
+        };
+    }
+    if header.starts_with(b"OggS") && header.len() >= 36 {
+        let codec = &header[28..];
+        if codec.starts_with(b"OpusHead") {
+            return Some(AudioFormat::Opus);
+        } else if codec.starts_with(b"\x01vorbis") {
+            return Some(AudioFormat::Vorbis);
+        } else if codec.starts_with(b"\x7fFLAC") {
+            return Some(AudioFormat::Flac);
+        }
+        return None;
+    }
+    if header.starts_with(b"fLaC") {
+        return Some(AudioFormat::Flac);
+    }
+    if header.starts_with(&[0x1a, 0x45, 0xdf, 0xa3]) {
+        return Some(AudioFormat::Mka);
+    }
+    if header.len() >= 12 && header.starts_with(b"RIFF") && &header[8..12] == b"WAVE" {
+        return Some(AudioFormat::Wav);
+    }
+    if header.len() >= 2 && header[0] == 0xff && header[1] & 0xf6 == 0xf0 {
+        // ADTS uses layer 0, MPEG audio frames use layers 1-3.
+        return Some(AudioFormat::Aac);
+    }
+    if is_mpeg_frame(header) {
+        return Some(AudioFormat::Mp3);
+    }
+
+    None
+}
+
+fn is_mpeg_frame(header: &[u8]) -> bool {
+    // FF FE is also the UTF-16LE byte order mark, which would pass as a Layer I frame.
+    if header.len() < 4 || header[0] != 0xff || header[1] == 0xfe {
+        return false;
+    }
+    let version = (header[1] >> 3) & 0x03;
+    let layer = (header[1] >> 1) & 0x03;
+    let bitrate = header[2] >> 4;
+    let sample_rate = (header[2] >> 2) & 0x03;
+
+    header[1] & 0xe0 == 0xe0
+        && version != 0x01
+        && layer != 0x00
+        && bitrate != 0x00
+        && bitrate != 0x0f
+        && sample_rate != 0x03
+}
+
+pub fn detect(path: &std::path::Path) -> Result<AudioFormat, String> {
+    let name = path.to_string_lossy();
+    if !path.exists() {
+        return Err(format!("{name} does not exist"));
+    }
+    if !path.is_file() {
+        return Err(format!("{name} is not a file"));
+    }
+
+    // Content is only sniffed for files whose extension is not a known audio one.
+    if let Some(format) = format_from_extension(path) {
+        return Ok(format);
+    }
+
+    let mut header = vec![];
+    std::fs::File::open(path)
+        .and_then(|f| f.take(64).read_to_end(&mut header))
+        .map_err(|err| format!("cannot read {name}: {err}"))?;
+
+    if let Some(format) = sniff(&header) {
+        return Ok(format);
+    }
+
+    match path.extension() {
+        Some(ext) => Err(format!(
+            "{name}: unsupported extension .{} and unrecognized content",
+            ext.to_string_lossy()
+        )),
+        None => Err(format!("{name}: unrecognized content")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniff_mpeg_frame() {
+        assert_eq!(sniff(&[0xff, 0xfb, 0x90, 0x64]), Some(AudioFormat::Mp3));
+        assert_eq!(sniff(b"ID3\x04\x00"), Some(AudioFormat::Mp3));
+        assert_eq!(sniff(&[0xff, 0xf1, 0x50, 0x80]), Some(AudioFormat::Aac));
+    }
+
+    #[test]
+    fn sniff_invalid_mpeg_frame() {
+        // Bitrate index 15, bitrate index 0 and sample rate index 3.
+        assert_eq!(sniff(&[0xff, 0xfb, 0xf0, 0x64]), None);
+        assert_eq!(sniff(&[0xff, 0xfb, 0x00, 0x64]), None);
+        assert_eq!(sniff(&[0xff, 0xfb, 0x9c, 0x64]), None);
+        assert_eq!(sniff(&[0xff, 0xfb]), None);
+    }
+
+    #[test]
+    fn sniff_utf16_text() {
+        let text: Vec<u8> = [0xff, 0xfe]
+            .into_iter()
+            .chain(
+                "[00:01.00]Chapter One"
+                    .encode_utf16()
+                    .flat_map(u16::to_le_bytes),
+            )
+            .collect();
+        assert_eq!(sniff(&text), None);
+
+        let text: Vec<u8> = [0xff, 0xfe]
+            .into_iter()
+            .chain(
+                "REM GENRE Audiobook"
+                    .encode_utf16()
+                    .flat_map(u16::to_le_bytes),
+            )
+            .collect();
+        assert_eq!(sniff(&text), None);
+    }
+}
@@ -0,0 +1,84 @@
+const HISTORY_MAX_LEN: usize = 50;
+// Jumps following each other this quickly, like a held seek key, share one origin.
+const HISTORY_MERGE_SECS: i64 = 2;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct HistoryEntry {
+    pub time: f64,
+    pub at: i64,
+    pub chapter: Option<String>,
+}
+
+impl HistoryEntry {
+    pub fn new(time: f64, chapter: Option<String>) -> Self {
+        HistoryEntry {
+            time,
+            at: chrono::Utc::now().timestamp(),
+            chapter,
+        }
+    }
+
+    pub fn at_str(&self) -> String {
+        chrono::DateTime::from_timestamp(self.at, 0)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PositionHistory {
+    undo: Vec<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+    last_record: i64,
+}
+
+impl PositionHistory {
+    pub fn new(entries: Vec<HistoryEntry>) -> Self {
+        PositionHistory {
+            undo: entries,
+            ..Default::default()
+        }
+    }
+
+    pub fn record(&mut self, origin: HistoryEntry) {
+        self.redo.clear();
+        let merge = origin.at - self.last_record < HISTORY_MERGE_SECS;
+        self.last_record = origin.at;
+        if merge && !self.undo.is_empty() {
+            return;
+        }
+        self.undo.push(origin);
+        if self.undo.len() > HISTORY_MAX_LEN {
+            self.undo.remove(0);
+        }
+    }
+
+    pub fn undo(&mut self, current: HistoryEntry) -> Option<HistoryEntry> {
+        let origin = self.undo.pop()?;
+        self.redo.push(current);
+        self.last_record = 0;
+
+        Some(origin)
+    }
+
+    pub fn redo(&mut self, current: HistoryEntry) -> Option<HistoryEntry> {
+        let target = self.redo.pop()?;
+        self.undo.push(current);
+        self.last_record = 0;
+
+        Some(target)
+    }
+
+    // Most recent jump origin first.
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        self.undo.iter().rev().cloned().collect()
+    }
+
+    pub fn stored(&self) -> Vec<HistoryEntry> {
+        self.undo.clone()
+    }
+}
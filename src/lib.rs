@@ -1,22 +1,36 @@
+pub mod book;
+pub mod bookmarks;
+pub mod chapters;
+pub mod config;
+pub mod cue;
+pub mod eq;
+pub mod export;
+pub mod format;
+pub mod history;
 pub mod libmpv_handler;
+pub mod library;
 pub mod logger;
 pub mod mc_os_interface;
+pub mod progress;
+pub mod queue;
+pub mod recent;
+pub mod timer;
+pub mod transcript;
 pub mod tui;
 
 use crate::libmpv_handler::{LibMpvEventMessage, LibMpvMessage};
-#[cfg(not(target_os = "linux"))]
-use std::io::Write;
 
 #[derive(Debug)]
 pub enum UAPlayerError {
     InvalidOption(String),
     InvalidOptionsStructure,
-    InvalidFile,
+    InvalidFile(String),
+    InvalidConfig(String),
     SouvlakiError(souvlaki::Error),
     SystemTimeError(std::time::SystemTimeError),
     IOError(std::io::Error),
     LibMpvMessageSendError(crossbeam::channel::SendError<LibMpvMessage>),
-    LibMpvEventMessageSendError(crossbeam::channel::SendError<LibMpvEventMessage>),
+    LibMpvEventMessageSendError(Box<crossbeam::channel::SendError<LibMpvEventMessage>>),
     LibMpvError(libmpv2::Error),
 }
 
@@ -46,7 +60,7 @@ impl From<crossbeam::channel::SendError<LibMpvMessage>> for UAPlayerError {
 
 impl From<crossbeam::channel::SendError<LibMpvEventMessage>> for UAPlayerError {
     fn from(err: crossbeam::channel::SendError<LibMpvEventMessage>) -> Self {
-        UAPlayerError::LibMpvEventMessageSendError(err)
+        UAPlayerError::LibMpvEventMessageSendError(Box::new(err))
     }
 }
 
@@ -61,16 +75,34 @@ pub enum ProgramOption {
     PATH(String),
     PrintHelp,
     Volume(i64),
+    AudioDevice(String),
     Verbose,
+    ExportSplit(String),
+    ExportMerge(String),
 }
 
 pub fn process_args() -> Result<Vec<ProgramOption>, UAPlayerError> {
     let mut options = vec![];
     let mut args: Vec<String> = std::env::args().skip(1).collect();
 
+    if args.first().is_some_and(|a| a == "export") {
+        return process_export_args(&args[1..]);
+    }
+
+    if let Some(i) = args
+        .iter()
+        .position(|a| a == "--recent" || a.starts_with("--recent="))
+    {
+        let arg = args.remove(i);
+        if args.iter().any(|a| !a.starts_with("--")) {
+            return Err(UAPlayerError::InvalidOptionsStructure);
+        }
+        args.push(recent::pick_recent(&arg)?);
+    }
+
     let mut last_arg = args
         .pop()
-        .or_else(|| load_path_from_config())
+        .or_else(load_last_path)
         .ok_or(UAPlayerError::InvalidOptionsStructure)?;
 
     if last_arg == "--help" {
@@ -80,17 +112,18 @@ pub fn process_args() -> Result<Vec<ProgramOption>, UAPlayerError> {
 
     if last_arg.starts_with("--") {
         args.push(last_arg);
-        last_arg = load_path_from_config().ok_or(UAPlayerError::InvalidOptionsStructure)?;
+        last_arg = load_last_path().ok_or(UAPlayerError::InvalidOptionsStructure)?;
     }
 
     let file_path = last_arg;
     let abs_file_path = std::path::absolute(&file_path)?;
     if !abs_file_path.try_exists()? {
-        return Err(UAPlayerError::InvalidFile);
-    }
-    if !is_audiofile(&abs_file_path) {
-        return Err(UAPlayerError::InvalidFile);
+        return Err(UAPlayerError::InvalidFile(format!(
+            "{} does not exist",
+            abs_file_path.to_string_lossy()
+        )));
     }
+    book::Book::open(&abs_file_path)?;
 
     options.push(ProgramOption::PATH(
         abs_file_path.to_string_lossy().to_string(),
@@ -111,6 +144,12 @@ pub fn process_args() -> Result<Vec<ProgramOption>, UAPlayerError> {
                     Err(UAPlayerError::InvalidOption(arg))
                 }
             }
+            s if s.starts_with("--audio-device=") => match s.split_once('=') {
+                Some((_, device)) if !device.is_empty() => {
+                    Ok(ProgramOption::AudioDevice(device.to_string()))
+                }
+                _ => Err(UAPlayerError::InvalidOption(arg)),
+            },
             _ => Err(UAPlayerError::InvalidOption(arg)),
         };
         options.push(arg?);
@@ -119,89 +158,60 @@ pub fn process_args() -> Result<Vec<ProgramOption>, UAPlayerError> {
     Ok(options)
 }
 
-#[cfg(target_os = "linux")]
-fn load_path_from_config() -> Option<String> {
-    let config_file_path = std::env::var("XDG_CONFIG_HOME")
-        .or(std::env::var("HOME").map(|s| format!("{s}/.config")))
-        .map(|path| format!("{path}/{}/config", env!("CARGO_PKG_NAME")));
-    if let Ok(path) = config_file_path {
-        if std::path::PathBuf::from(&path).is_file() {
-            return std::fs::read_to_string(path).ok();
-        }
-    }
-
-    None
-}
-
-#[cfg(target_os = "linux")]
-pub fn save_path_to_config(path: &str) {
-    let config_dir_path = std::env::var("XDG_CONFIG_HOME")
-        .or(std::env::var("HOME").map(|s| format!("{s}/.config")))
-        .map(|path| format!("{path}/{}", env!("CARGO_PKG_NAME")));
-    if let Ok(dir_path) = config_dir_path {
-        if !std::path::PathBuf::from(&dir_path).is_dir() {
-            std::fs::create_dir(dir_path.clone()).unwrap();
-        }
-        let config_file_path = format!("{dir_path}/config");
-        std::fs::write(config_file_path, path).unwrap();
-    }
-}
-
-#[cfg(target_os = "windows")]
-fn load_path_from_config() -> Option<String> {
-    let config_file_path =
-        std::env::var("APPDATA").map(|path| format!("{path}/{}/config", env!("CARGO_PKG_NAME")));
-    if let Ok(path) = config_file_path {
-        if std::path::PathBuf::from(&path).is_file() {
-            return std::fs::read_to_string(path).ok();
-        }
+// export <split|merge> <output> [PATH] [--verbose]
+fn process_export_args(args: &[String]) -> Result<Vec<ProgramOption>, UAPlayerError> {
+    let mut options = vec![];
+    let mut args = args.iter();
+    let (Some(mode), Some(output)) = (args.next(), args.next()) else {
+        return Err(UAPlayerError::InvalidOptionsStructure);
+    };
+    match mode.as_str() {
+        "split" => options.push(ProgramOption::ExportSplit(output.clone())),
+        "merge" => options.push(ProgramOption::ExportMerge(output.clone())),
+        _ => return Err(UAPlayerError::InvalidOption(mode.clone())),
     }
 
-    None
-}
-
-#[cfg(target_os = "windows")]
-pub fn save_path_to_config(path: &str) {
-    let config_dir_path =
-        std::env::var("APPDATA").map(|path| format!("{path}/{}", env!("CARGO_PKG_NAME")));
-    if let Ok(dir_path) = config_dir_path {
-        if !std::path::PathBuf::from(&dir_path).is_dir() {
-            std::fs::create_dir(dir_path.clone()).unwrap();
+    let mut path = None;
+    for arg in args {
+        match arg.as_str() {
+            "--verbose" => options.push(ProgramOption::Verbose),
+            s if s.starts_with("--") => return Err(UAPlayerError::InvalidOption(arg.clone())),
+            _ if path.is_some() => return Err(UAPlayerError::InvalidOptionsStructure),
+            _ => path = Some(arg.clone()),
         }
-        let config_file_path = format!("{dir_path}/config");
-        std::fs::write(config_file_path, path).unwrap();
     }
-}
+    let path = path
+        .or_else(load_last_path)
+        .ok_or(UAPlayerError::InvalidOptionsStructure)?;
+    let abs_path = std::path::absolute(&path)?;
+    book::Book::open(&abs_path)?;
+    options.push(ProgramOption::PATH(abs_path.to_string_lossy().to_string()));
 
-#[cfg(not(any(target_os = "linux", target_os = "windows")))]
-fn load_path_from_config() -> Option<String> {
-    std::fs::read_to_string("last.txt").ok()
+    Ok(options)
 }
 
-#[cfg(not(any(target_os = "linux", target_os = "windows")))]
-pub fn save_path_to_config(path: &str) {
-    let mut file = std::fs::File::create(format!("last.txt")).unwrap();
-    file.write_all(path.as_bytes()).unwrap();
-    log::debug!("File path: {path}");
+fn load_last_path() -> Option<String> {
+    recent::load_recent().first().map(|e| e.path.clone())
 }
 
 pub fn print_help() {
     println!("Usage: {} [OPTIONS] [PATH]", env!("CARGO_PKG_NAME"));
+    println!("       PATH can be an audiobook file, a directory of audio files or an m3u playlist");
+    println!(
+        "       {} export <split|merge> <OUTPUT> [PATH]",
+        env!("CARGO_PKG_NAME")
+    );
+    println!("       split writes every chapter to OUTPUT directory as an mp3 file");
+    println!("       merge joins the parts of a book into OUTPUT (m4b) with a chapter per part");
     println!("       {} --help", env!("CARGO_PKG_NAME"));
     println!("Options:");
     println!("\t --volume=<value>\t(0..100)");
+    println!("\t --audio-device=<name>\tmpv audio output device");
+    println!("\t --recent[=<n>]\t\tpick a recently played book");
     println!("\t --verbose");
     println!("\t --help");
 }
 
-fn is_audiofile(path: &std::path::PathBuf) -> bool {
-    if let Some(ext) = path.extension() {
-        if ext == "m4b" {
-            return true;
-        } else if ext == "mp3" {
-            return true;
-        }
-    }
-
-    false
+fn is_audiofile(path: &std::path::Path) -> bool {
+    format::detect(path).is_ok()
 }
@@ -1,11 +1,26 @@
 use crate::UAPlayerError;
-use std::io::Write;
+use crate::book::{Book, BookPosition};
+use crate::bookmarks::{Bookmark, insert_bookmark};
+use crate::chapters::{ChaptersCache, detect_silence_chapters, interval_chapters};
+use crate::config::{Config, FallbackChaptersMode, NormalizationMode};
+use crate::eq::{FLAT_PRESET, eq_filter, preset_bands};
+use crate::export::{ClipStatus, clip_path, export_clip};
+use crate::history::{HistoryEntry, PositionHistory};
+use crate::progress::ProgressStore;
+use crate::queue::{load_queue, save_queue};
+use crate::recent::record_recent;
+use crate::timer::{
+    SleepTimer, TimerAction, TimerCountdown, TimerLength, TimerRequest, TimerStatus,
+};
+use crate::transcript::{TranscriptLine, load_transcript};
 
 #[derive(Debug)]
 pub enum LibMpvMessage {
     Quit,
     UpdateVolume(i64),
     SetVolume(i64),
+    UpdateSpeed(f64),
+    SetSpeed(f64),
     UpdatePosition(f64),
     SetPosition(f64),
     Resume,
@@ -13,6 +28,29 @@ pub enum LibMpvMessage {
     PlayPause,
     NextChapter,
     PrevChapter,
+    LoadBook(String),
+    UndoSeek,
+    RedoSeek,
+    AddBookmark(String),
+    ExportClip((f64, f64, Option<String>)),
+    DeleteBookmark(usize),
+    SkipSilence(bool),
+    Normalization(NormalizationMode),
+    Compressor(bool),
+    Eq(String),
+    ListAudioDevices,
+    SetAudioDevice(String),
+    Mono(bool),
+    Balance(f64),
+    AddTimer(TimerRequest),
+    CancelTimer(Option<String>),
+    ExtendTimers,
+    LoopA,
+    LoopB,
+    LoopClear,
+    RepeatChapter(Option<bool>),
+    QueueAdd(String),
+    QueueClear,
 }
 
 #[derive(Debug)]
@@ -23,8 +61,24 @@ pub enum LibMpvEventMessage {
     PlaybackResume,
     FileLoaded(FileLoadedData),
     VolumeUpdate(i64),
+    SpeedUpdate(f64),
+    SkipSilenceUpdate(bool),
+    NormalizationUpdate((NormalizationMode, bool)),
+    EqUpdate(String),
+    AudioDevicesUpdate((Vec<AudioDevice>, String)),
+    ChannelsUpdate((bool, f64)),
+    TimersUpdate((Vec<TimerStatus>, bool)),
+    TimeSavedUpdate(f64),
+    LoopUpdate(LoopData),
     PositionUpdate(f64),
     ChapterUpdate((String, usize)),
+    ChaptersUpdate(Vec<Chapter>),
+    HistoryUpdate(Vec<HistoryEntry>),
+    BookmarksUpdate(Vec<Bookmark>),
+    TranscriptUpdate(Vec<TranscriptLine>),
+    QueueUpdate(Vec<String>),
+    Status(String),
+    Error(String),
     Quit,
 }
 
@@ -35,31 +89,124 @@ pub struct FileLoadedData {
     pub album: Option<String>,
     pub duration: f64,
     pub volume: i64,
+    pub speed: f64,
     pub chapter: (Option<String>, usize),
     pub chapters: Vec<Chapter>,
 }
 
-#[derive(serde::Deserialize, Debug, Clone)]
+#[derive(Debug, Clone)]
+pub struct AudioDevice {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoopData {
+    pub a: Option<f64>,
+    pub b: Option<f64>,
+    pub repeat_chapter: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct Chapter {
     pub title: String,
     pub time: f32,
 }
 
+#[derive(Debug, Clone)]
+pub struct ProbeData {
+    pub title: String,
+    pub artist: Option<String>,
+    pub duration: f64,
+}
+
 pub struct LibMpvHandler {
     mpv: libmpv2::Mpv,
     chapters: Vec<Chapter>,
+    part_starts: Vec<f64>,
+    external_chapters: bool,
+    current_chapter: Option<usize>,
+    config: Config,
+    progress: ProgressStore,
+    chapters_cache: ChaptersCache,
+    silence_r: Option<crossbeam::channel::Receiver<(String, Vec<Chapter>)>>,
+    queue: Vec<String>,
+    skip_silence: bool,
+    normalization: NormalizationMode,
+    compressor: bool,
+    eq: String,
+    mono: bool,
+    balance: f64,
+    time_saved: f64,
+    time_saved_sample: Option<(std::time::Instant, f64)>,
+    paused_at: Option<std::time::Instant>,
+    timers: Vec<SleepTimer>,
+    timers_tick: std::time::Instant,
+    timers_changed: bool,
+    fade: Option<Fade>,
+    faded: Option<Fade>,
+    ab_loop: LoopData,
+    history: PositionHistory,
+    history_changed: bool,
+    clip_r: Option<crossbeam::channel::Receiver<ClipStatus>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Fade {
+    start: std::time::Instant,
+    duration: f64,
+    volume: i64,
+    position: Option<f64>,
 }
 
 impl LibMpvHandler {
-    pub fn initialize_libmpv(volume: i64) -> Result<Self, libmpv2::Error> {
+    pub fn initialize_libmpv(volume: i64, config: Config) -> Result<Self, libmpv2::Error> {
         let mpv = libmpv2::Mpv::new()?;
         mpv.set_property("volume", volume)?;
         mpv.set_property("vo", "null")?;
+        mpv.set_property("audio-pitch-correction", true)?;
+        if let Some(device) = &config.audio.device {
+            mpv.set_property("audio-device", device.as_str())?;
+        }
 
-        Ok(LibMpvHandler {
+        let mut handler = LibMpvHandler {
             mpv,
             chapters: vec![],
-        })
+            part_starts: vec![],
+            external_chapters: false,
+            current_chapter: None,
+            config,
+            progress: ProgressStore::load(),
+            chapters_cache: ChaptersCache::load(),
+            silence_r: None,
+            queue: load_queue(),
+            skip_silence: false,
+            normalization: NormalizationMode::Off,
+            compressor: false,
+            eq: FLAT_PRESET.to_string(),
+            mono: false,
+            balance: 0.0,
+            time_saved: 0.0,
+            time_saved_sample: None,
+            paused_at: None,
+            timers: vec![],
+            timers_tick: std::time::Instant::now(),
+            timers_changed: false,
+            fade: None,
+            faded: None,
+            ab_loop: LoopData::default(),
+            history: PositionHistory::default(),
+            history_changed: false,
+            clip_r: None,
+        };
+        if handler.config.skip_silence.enabled {
+            handler.set_skip_silence(true)?;
+        }
+        handler.set_normalization(handler.config.normalization.mode)?;
+        handler.set_compressor(handler.config.normalization.compressor)?;
+        handler.set_channels(handler.config.audio.mono, handler.config.audio.balance)?;
+
+        Ok(handler)
     }
 
     pub fn create_client(&self) -> Result<libmpv2::Mpv, libmpv2::Error> {
@@ -68,6 +215,7 @@ impl LibMpvHandler {
 
         client.observe_property("pause", libmpv2::Format::Flag, 0)?;
         client.observe_property("volume", libmpv2::Format::Int64, 0)?;
+        client.observe_property("speed", libmpv2::Format::Double, 0)?;
         client.observe_property("chapter", libmpv2::Format::Int64, 0)?;
 
         Ok(client)
@@ -75,30 +223,831 @@ impl LibMpvHandler {
 
     pub fn load_file(&self, file: &str) -> Result<(), libmpv2::Error> {
         self.mpv
-            .command("loadfile", &[format!("{file}").as_str(), "append-play"])
+            .command("loadfile", &[format!("{file}").as_str(), "replace"])
     }
 
-    pub fn fech_chapters(&mut self) -> Result<(), libmpv2::Error> {
-        let chapters = self.mpv.get_property::<libmpv2::MpvStr>("chapter-list")?;
-        let chapters: Vec<Chapter> = serde_json::from_str(chapters.trim()).unwrap_or(vec![]);
+    fn set_audio_filter(&self, label: &str, filter: Option<&str>) -> Result<(), libmpv2::Error> {
+        // Removing a filter that is not in the chain fails, which is fine here.
+        let _ = self.mpv.command("af", &["remove", &format!("@{label}")]);
+        if let Some(filter) = filter {
+            self.mpv
+                .command("af", &["add", &format!("@{label}:{filter}")])?;
+        }
+
+        Ok(())
+    }
+
+    fn set_skip_silence(&mut self, enabled: bool) -> Result<(), libmpv2::Error> {
+        let config = &self.config.skip_silence;
+        let filter = format!(
+            "lavfi=[silenceremove=stop_periods=-1:stop_duration={gap}:stop_silence={gap}:stop_threshold={}dB]",
+            config.threshold,
+            gap = config.min_gap
+        );
+        self.set_audio_filter("skipsilence", enabled.then_some(filter.as_str()))?;
+        self.skip_silence = enabled;
+        self.time_saved_sample = None;
+
+        Ok(())
+    }
+
+    fn set_normalization(&mut self, mode: NormalizationMode) -> Result<(), libmpv2::Error> {
+        let filter = match mode {
+            NormalizationMode::Off => None,
+            NormalizationMode::Loudnorm => Some(format!(
+                "lavfi=[loudnorm=I={}:TP=-1.5:LRA=11]",
+                self.config.normalization.target
+            )),
+            NormalizationMode::Dynaudnorm => Some("lavfi=[dynaudnorm=f=500:g=31]".to_string()),
+        };
+        self.set_audio_filter("normalization", filter.as_deref())?;
+        self.normalization = mode;
+
+        Ok(())
+    }
+
+    fn set_compressor(&mut self, enabled: bool) -> Result<(), libmpv2::Error> {
+        let config = &self.config.normalization;
+        let filter = format!(
+            "lavfi=[acompressor=threshold={}dB:ratio={}:attack=20:release=250:makeup=2]",
+            config.compressor_threshold, config.compressor_ratio
+        );
+        self.set_audio_filter("compressor", enabled.then_some(filter.as_str()))?;
+        self.compressor = enabled;
+
+        Ok(())
+    }
+
+    fn audio_devices(&self) -> Vec<AudioDevice> {
+        let count = self
+            .mpv
+            .get_property::<i64>("audio-device-list/count")
+            .unwrap_or(0);
+
+        (0..count)
+            .filter_map(|i| {
+                let name = self
+                    .mpv
+                    .get_property::<String>(&format!("audio-device-list/{i}/name"))
+                    .ok()?;
+                let description = self
+                    .mpv
+                    .get_property::<String>(&format!("audio-device-list/{i}/description"))
+                    .unwrap_or_else(|_| name.clone());
+                Some(AudioDevice { name, description })
+            })
+            .collect()
+    }
+
+    // Downmixes to mono and scales the channels, negative balance favours the left one.
+    fn set_channels(&mut self, mono: bool, balance: f64) -> Result<(), libmpv2::Error> {
+        let balance = ((balance * 100.0).round() / 100.0).clamp(-1.0, 1.0);
+        let left = 1.0 - balance.max(0.0);
+        let right = 1.0 + balance.min(0.0);
+        let channel = |gain: f64, c: &str| {
+            if mono {
+                format!("{}*c0+{}*c1", gain / 2.0, gain / 2.0)
+            } else {
+                format!("{gain}*{c}")
+            }
+        };
+        let filter = format!(
+            "lavfi=[pan=stereo|c0={}|c1={}]",
+            channel(left, "c0"),
+            channel(right, "c1")
+        );
+        let enabled = mono || balance != 0.0;
+        self.set_audio_filter("channels", enabled.then_some(filter.as_str()))?;
+        self.mono = mono;
+        self.balance = balance;
+
+        Ok(())
+    }
+
+    fn set_eq(&mut self, preset: &str) -> Result<bool, libmpv2::Error> {
+        let Some(bands) = preset_bands(preset, &self.config.eq) else {
+            return Ok(false);
+        };
+        self.set_audio_filter("eq", eq_filter(&bands).as_deref())?;
+        self.eq = preset.to_string();
+
+        Ok(true)
+    }
+
+    fn set_book_eq(&mut self, book: &Book, preset: &str) -> Result<bool, UAPlayerError> {
+        if !self.set_eq(preset)? {
+            return Ok(false);
+        }
+
+        let mut progress = self.progress.get(book);
+        progress.eq = Some(preset.to_string());
+        self.progress.update(book, progress);
+        self.progress.save()?;
+
+        Ok(true)
+    }
+
+    // The time saved is how much faster the book advanced than the wall clock.
+    fn update_time_saved(&mut self) -> Option<f64> {
+        let now = std::time::Instant::now();
+        if let Some((at, _)) = self.time_saved_sample
+            && now.duration_since(at) < std::time::Duration::from_secs(1)
+        {
+            return None;
+        }
+        if !self.skip_silence || self.mpv.get_property::<bool>("pause").unwrap_or(true) {
+            self.time_saved_sample = None;
+            return None;
+        }
+        let time = self.mpv.get_property::<f64>("time-pos/full").ok()?;
+        let speed = self.mpv.get_property::<f64>("speed").unwrap_or(1.0);
+
+        let (at, prev_time) = self.time_saved_sample.replace((now, time))?;
+        let saved = (time - prev_time) / speed - now.duration_since(at).as_secs_f64();
+        if saved <= 0.05 {
+            return None;
+        }
+        self.time_saved += saved;
+
+        Some(self.time_saved)
+    }
+
+    pub fn fech_chapters(&mut self, book: &Book) -> Result<(), libmpv2::Error> {
+        let BookChapters {
+            mut chapters,
+            part_starts,
+            external,
+        } = read_chapters(&self.mpv, book)?;
+
+        self.part_starts = part_starts;
+        self.external_chapters = external;
+        self.current_chapter = None;
+        if chapters.is_empty() {
+            chapters = self.fallback_chapters(book);
+            self.external_chapters = !chapters.is_empty();
+        }
 
         self.chapters = chapters;
 
         Ok(())
     }
 
+    fn fallback_chapters(&mut self, book: &Book) -> Vec<Chapter> {
+        let config = self.config.fallback_chapters.clone();
+        if config.mode == FallbackChaptersMode::None {
+            return vec![];
+        }
+        if config.mode == FallbackChaptersMode::Silence {
+            if let Some(chapters) = self.chapters_cache.get(&book.path, &config) {
+                return chapters;
+            }
+
+            // Interval chapters are shown until the scan of the whole book finishes.
+            let (silence_s, silence_r) = crossbeam::channel::unbounded();
+            let (path, mpv_path) = (book.path.clone(), book.mpv_path());
+            std::thread::spawn(move || match detect_silence_chapters(&mpv_path, &config) {
+                Ok(chapters) => {
+                    let _ = silence_s.send((path, chapters));
+                }
+                Err(err) => log::error!("LibMpv: silence detection of {path} failed: {err:?}"),
+            });
+            self.silence_r = Some(silence_r);
+        }
+
+        let duration = self.mpv.get_property::<f64>("duration/full").unwrap_or(0.0);
+        interval_chapters(duration, self.config.fallback_chapters.interval)
+    }
+
+    fn receive_silence_chapters(&mut self, book: &Book) -> Result<bool, UAPlayerError> {
+        let Some(silence_r) = &self.silence_r else {
+            return Ok(false);
+        };
+        let (path, chapters) = match silence_r.try_recv() {
+            Ok(result) => result,
+            Err(crossbeam::channel::TryRecvError::Empty) => return Ok(false),
+            Err(crossbeam::channel::TryRecvError::Disconnected) => {
+                self.silence_r = None;
+                return Ok(false);
+            }
+        };
+        self.silence_r = None;
+
+        self.chapters_cache
+            .insert(&path, &self.config.fallback_chapters, chapters.clone());
+        self.chapters_cache.save()?;
+        if path != book.path || !self.external_chapters || chapters.is_empty() {
+            return Ok(false);
+        }
+        self.chapters = chapters;
+        self.current_chapter = None;
+
+        Ok(true)
+    }
+
+    fn chapter_at(&self, time: f64) -> Option<usize> {
+        self.chapters.iter().rposition(|c| c.time as f64 <= time)
+    }
+
+    fn update_external_chapter(&mut self) -> Option<usize> {
+        if !self.external_chapters {
+            return None;
+        }
+        let time = self.mpv.get_property::<f64>("time-pos/full").ok()?;
+        let chapter_num = self.chapter_at(time);
+        if chapter_num == self.current_chapter {
+            return None;
+        }
+        self.current_chapter = chapter_num;
+
+        chapter_num
+    }
+
+    fn resolve_position(&self, book: &Book, position: &BookPosition) -> f64 {
+        if let Some((name, offset)) = &position.part {
+            let part = (0..book.parts.len()).find(|&i| &book.part_name(i) == name);
+            if let Some(start) = part.and_then(|i| self.part_starts.get(i)) {
+                return (start + offset).max(0.0);
+            }
+        }
+
+        position.time
+    }
+
+    fn open_book(&mut self, book: &Book) -> Result<BookPosition, UAPlayerError> {
+        let progress = self.progress.get(book);
+        // Running timers carry over to the next book, otherwise the book's saved ones resume.
+        self.timers
+            .retain(|t| matches!(t.countdown, TimerCountdown::Time { .. }));
+        if self.timers.is_empty() {
+            self.timers = progress.timers.clone();
+        }
+        self.timers_changed = true;
+        self.history = PositionHistory::new(progress.history.clone());
+        self.history_changed = true;
+        self.ab_loop.repeat_chapter = false;
+        self.set_ab_loop(None, None)?;
+        record_recent(&book.path, progress.position);
+        self.mpv
+            .set_property("speed", progress.speed.unwrap_or(1.0))?;
+        let eq = progress
+            .eq
+            .clone()
+            .unwrap_or_else(|| self.config.eq.default.clone());
+        if !self.set_eq(&eq)? {
+            log::error!("LibMpv: unknown eq preset {eq}");
+            self.set_eq(FLAT_PRESET)?;
+        }
+        self.load_file(&book.mpv_path())?;
+        self.paused_at = None;
+
+        let mut position = progress.book_position();
+        if progress.last_played > 0 {
+            let since = (chrono::Utc::now().timestamp() - progress.last_played) as f64;
+            let rewind = self.config.smart_rewind.rewind_after(since);
+            position.time = (position.time - rewind).max(0.0);
+            if let Some((_, offset)) = &mut position.part {
+                *offset -= rewind;
+            }
+        }
+
+        Ok(position)
+    }
+
+    fn save_progress(&mut self, book: &Book) -> Result<(), UAPlayerError> {
+        let Ok(pos) = self.mpv.get_property::<f64>("time-pos/full") else {
+            return Ok(());
+        };
+        // What played while a sleep timer faded out was probably missed.
+        let pos = self
+            .fade
+            .or(self.faded)
+            .and_then(|f| f.position)
+            .filter(|_| self.config.sleep_timer.still_listening)
+            .map_or(pos, |p| p.min(pos));
+        let position = self.book_position(book, pos);
+
+        let mut progress = self.progress.get(book);
+        progress.set_book_position(&position);
+        // A finished book played again is in progress once more.
+        if pos > 0.0 {
+            progress.finished = false;
+        }
+        progress.duration = self
+            .mpv
+            .get_property::<f64>("duration/full")
+            .unwrap_or(progress.duration);
+        progress.last_played = chrono::Utc::now().timestamp();
+        progress.timers = self.timers.clone();
+        progress.history = self.history.stored();
+        self.progress.update(book, progress);
+        self.progress.save()?;
+
+        if self.config.write_sidecars {
+            book.save_sidecar(&position).unwrap_or_else(|err| {
+                log::error!("LibMpv: cannot write sidecar of {}: {err:?}", book.path)
+            });
+        }
+        record_recent(&book.path, pos);
+
+        Ok(())
+    }
+
+    fn set_speed(&mut self, book: &Book, speed: f64) -> Result<(), UAPlayerError> {
+        let speed = ((speed * 100.0).round() / 100.0).clamp(0.25, 4.0);
+        self.mpv.set_property("speed", speed)?;
+
+        let mut progress = self.progress.get(book);
+        progress.speed = Some(speed);
+        self.progress.update(book, progress);
+        self.progress.save()?;
+
+        Ok(())
+    }
+
+    fn rewind_after_pause(&mut self) -> Result<(), libmpv2::Error> {
+        let Some(paused_at) = self.paused_at.take() else {
+            return Ok(());
+        };
+        let rewind = self
+            .config
+            .smart_rewind
+            .rewind_after(paused_at.elapsed().as_secs_f64());
+        if rewind > 0.0 {
+            self.mpv.command("seek", &[&(-rewind).to_string()])?;
+        }
+
+        Ok(())
+    }
+
+    fn history_entry(&self) -> Option<HistoryEntry> {
+        let time = self.mpv.get_property::<f64>("time-pos/full").ok()?;
+        let chapter = self
+            .chapter_at(time)
+            .and_then(|i| self.chapters.get(i))
+            .map(|c| c.title.clone());
+
+        Some(HistoryEntry::new(time, chapter))
+    }
+
+    fn record_jump(&mut self) {
+        if let Some(origin) = self.history_entry() {
+            self.history.record(origin);
+            self.history_changed = true;
+        }
+    }
+
+    fn add_bookmark(&mut self, book: &Book, note: String) -> Result<(), UAPlayerError> {
+        let Some(entry) = self.history_entry() else {
+            return Ok(());
+        };
+        let mut progress = self.progress.get(book);
+        insert_bookmark(
+            &mut progress.bookmarks,
+            Bookmark::new(entry.time, entry.chapter, note),
+        );
+        self.progress.update(book, progress);
+        self.progress.save()?;
+
+        Ok(())
+    }
+
+    fn delete_bookmark(&mut self, book: &Book, i: usize) -> Result<bool, UAPlayerError> {
+        let mut progress = self.progress.get(book);
+        if i >= progress.bookmarks.len() {
+            return Ok(false);
+        }
+        progress.bookmarks.remove(i);
+        self.progress.update(book, progress);
+        self.progress.save()?;
+
+        Ok(true)
+    }
+
+    fn start_clip_export(
+        &mut self,
+        book: &Book,
+        start: f64,
+        end: f64,
+        path: Option<String>,
+    ) -> Result<String, String> {
+        if self.clip_r.is_some() {
+            return Err("a clip is already being exported".to_string());
+        }
+        if end <= start {
+            return Err("the clip must end after it starts".to_string());
+        }
+        let output = clip_path(path, &media_title(&self.mpv, book), start, end);
+
+        // Encoding runs on its own mpv handle so playback is not interrupted.
+        let (clip_s, clip_r) = crossbeam::channel::unbounded();
+        let (mpv_path, thread_output) = (book.mpv_path(), output.clone());
+        std::thread::spawn(move || {
+            if let Err(err) = export_clip(&mpv_path, start, end, &thread_output, &clip_s) {
+                log::error!("LibMpv: clip export to {thread_output} failed: {err:?}");
+                let _ = clip_s.send(ClipStatus::Failed(thread_output));
+            }
+        });
+        self.clip_r = Some(clip_r);
+
+        Ok(output)
+    }
+
+    fn receive_clip_status(&mut self) -> Option<String> {
+        let status = match self.clip_r.as_ref()?.try_recv() {
+            Ok(status) => status,
+            Err(crossbeam::channel::TryRecvError::Empty) => return None,
+            Err(crossbeam::channel::TryRecvError::Disconnected) => {
+                self.clip_r = None;
+                return None;
+            }
+        };
+
+        match status {
+            ClipStatus::Progress(percent) => Some(format!("Exporting clip {percent}%")),
+            ClipStatus::Done(output) => {
+                self.clip_r = None;
+                Some(format!("Clip exported to {output}"))
+            }
+            ClipStatus::Failed(output) => {
+                self.clip_r = None;
+                Some(format!("Error: cannot export clip to {output}"))
+            }
+        }
+    }
+
+    fn undo_jump(&mut self, redo: bool) -> Result<(), libmpv2::Error> {
+        let Some(current) = self.history_entry() else {
+            return Ok(());
+        };
+        let target = if redo {
+            self.history.redo(current)
+        } else {
+            self.history.undo(current)
+        };
+        if let Some(target) = target {
+            self.mpv
+                .command("seek", &[&target.time.to_string(), "absolute"])?;
+            self.history_changed = true;
+        }
+
+        Ok(())
+    }
+
+    fn current_chapter(&self) -> Option<usize> {
+        if self.external_chapters {
+            return self.current_chapter;
+        }
+        let chapter = self.mpv.get_property::<i64>("chapter").ok()?;
+
+        (chapter >= 0).then_some(chapter as usize)
+    }
+
+    fn set_ab_loop(&mut self, a: Option<f64>, b: Option<f64>) -> Result<(), libmpv2::Error> {
+        let point = |p: Option<f64>| p.map_or("no".to_string(), |p| p.to_string());
+        self.mpv.set_property("ab-loop-a", point(a))?;
+        self.mpv.set_property("ab-loop-b", point(b))?;
+        self.ab_loop.a = a;
+        self.ab_loop.b = b;
+
+        Ok(())
+    }
+
+    fn set_loop_point(&mut self, b: bool) -> Result<(), libmpv2::Error> {
+        let time = self.mpv.get_property::<f64>("time-pos/full")?;
+        self.ab_loop.repeat_chapter = false;
+        if b {
+            self.set_ab_loop(self.ab_loop.a.filter(|&a| a < time), Some(time))
+        } else {
+            self.set_ab_loop(Some(time), self.ab_loop.b.filter(|&b| b > time))
+        }
+    }
+
+    fn chapter_bounds(&self, chapter: usize) -> Option<(f64, f64)> {
+        let start = self.chapters.get(chapter)?.time as f64;
+        let end = match self.chapters.get(chapter + 1) {
+            Some(next) => next.time as f64,
+            None => self.mpv.get_property::<f64>("duration/full").ok()?,
+        };
+
+        Some((start, end))
+    }
+
+    fn set_repeat_chapter(&mut self, enabled: bool) -> Result<(), libmpv2::Error> {
+        self.ab_loop.repeat_chapter = enabled;
+        let bounds = self
+            .current_chapter()
+            .and_then(|i| self.chapter_bounds(i))
+            .filter(|_| enabled);
+        self.set_ab_loop(bounds.map(|b| b.0), bounds.map(|b| b.1))
+    }
+
+    // Moves the chapter loop along when the listener leaves the repeated chapter.
+    fn follow_repeat_chapter(&mut self, chapter: usize) -> Result<bool, libmpv2::Error> {
+        if !self.ab_loop.repeat_chapter {
+            return Ok(false);
+        }
+        let Some((start, end)) = self.chapter_bounds(chapter) else {
+            return Ok(false);
+        };
+        // Reaching the loop end briefly enters the next chapter before mpv seeks back.
+        let time = self.mpv.get_property::<f64>("time-pos/full")?;
+        if self.ab_loop.a == Some(start) || self.ab_loop.b.is_some_and(|b| (time - b).abs() < 0.5) {
+            return Ok(false);
+        }
+        self.set_ab_loop(Some(start), Some(end))?;
+
+        Ok(true)
+    }
+
+    fn add_timer(&mut self, request: TimerRequest) -> Result<(), String> {
+        let countdown = match request.length {
+            TimerLength::Minutes(min) => TimerCountdown::Time {
+                clock: request.clock.unwrap_or(self.config.sleep_timer.clock),
+                duration: (min * 60) as f64,
+                left: (min * 60) as f64,
+            },
+            TimerLength::Chapters(_) if self.chapters.is_empty() => {
+                return Err("no chapters to pause after".to_string());
+            }
+            TimerLength::Chapters(chapters) => TimerCountdown::Chapter {
+                chapters,
+                target: self.current_chapter().map_or(0, |i| i + 1) + chapters,
+            },
+        };
+        self.timers.retain(|t| t.name != request.name);
+        self.timers.push(SleepTimer {
+            name: request.name,
+            action: request.action,
+            countdown,
+        });
+        self.timers_changed = true;
+
+        Ok(())
+    }
+
+    fn cancel_timer(&mut self, name: Option<String>) -> Result<(), String> {
+        let len = self.timers.len();
+        match &name {
+            Some(name) => self.timers.retain(|t| &t.name != name),
+            None => self.timers.clear(),
+        }
+        if let Some(name) = name.filter(|_| self.timers.len() == len) {
+            return Err(format!("no timer named {name}"));
+        }
+        self.timers_changed = true;
+
+        Ok(())
+    }
+
+    // Chapter timers count down to the start of their target chapter, or the end of the book.
+    fn timer_left(&self, timer: &SleepTimer) -> f64 {
+        match &timer.countdown {
+            TimerCountdown::Time { left, .. } => left.max(0.0),
+            TimerCountdown::Chapter { target, .. } => {
+                let boundary = match self.chapters.get(*target) {
+                    Some(chapter) => chapter.time as f64,
+                    None => self.mpv.get_property::<f64>("duration/full").unwrap_or(0.0),
+                };
+                let time = self.mpv.get_property::<f64>("time-pos/full").unwrap_or(0.0);
+                let speed = self.mpv.get_property::<f64>("speed").unwrap_or(1.0);
+                ((boundary - time) / speed).max(0.0)
+            }
+        }
+    }
+
+    fn timer_statuses(&self) -> Vec<TimerStatus> {
+        self.timers
+            .iter()
+            .map(|t| TimerStatus {
+                name: t.name.clone(),
+                action: t.action,
+                kind: t.kind(),
+                left: self.timer_left(t),
+            })
+            .collect()
+    }
+
+    fn take_timers(&mut self, fired: impl Fn(&SleepTimer) -> bool) -> Option<TimerAction> {
+        let (fired, timers): (Vec<SleepTimer>, Vec<SleepTimer>) = std::mem::take(&mut self.timers)
+            .into_iter()
+            .partition(fired);
+        self.timers = timers;
+        if fired.is_empty() {
+            return None;
+        }
+        self.timers_changed = true;
+
+        fired
+            .into_iter()
+            .map(|t| t.action)
+            .max_by_key(|a| *a == TimerAction::Quit)
+    }
+
+    fn tick_timers(&mut self) -> Result<Option<TimerAction>, libmpv2::Error> {
+        let elapsed = self.timers_tick.elapsed().as_secs_f64();
+        self.timers_tick = std::time::Instant::now();
+        if self.timers.is_empty() {
+            return Ok(None);
+        }
+
+        let playing = !self.mpv.get_property::<bool>("pause").unwrap_or(true)
+            && self.mpv.get_property::<f64>("time-pos/full").is_ok();
+        self.timers
+            .iter_mut()
+            .for_each(|t| t.tick(elapsed, playing));
+        let action = self.take_timers(|t| match t.countdown {
+            TimerCountdown::Time { left, .. } => left <= 0.0,
+            TimerCountdown::Chapter { .. } => false,
+        });
+
+        let left = self
+            .timers
+            .iter()
+            .map(|t| self.timer_left(t))
+            .fold(f64::INFINITY, f64::min);
+        if action.is_none() && playing && left <= self.config.sleep_timer.fade {
+            if self.fade.is_none() {
+                self.timers_changed = true;
+            }
+            self.start_fade(left)?;
+        }
+
+        Ok(action)
+    }
+
+    fn chapter_timers_reached(&mut self, chapter: usize) -> Option<TimerAction> {
+        self.take_timers(|t| match t.countdown {
+            TimerCountdown::Chapter { target, .. } => target <= chapter,
+            TimerCountdown::Time { .. } => false,
+        })
+    }
+
+    // Any sign of life during the fade-out extends the timers about to fire.
+    fn extend_timers(&mut self) -> Result<(), libmpv2::Error> {
+        let fade = self.config.sleep_timer.fade;
+        let fading: Vec<bool> = self
+            .timers
+            .iter()
+            .map(|t| self.timer_left(t) <= fade)
+            .collect();
+        self.timers
+            .iter_mut()
+            .zip(fading)
+            .filter(|(_, fading)| *fading)
+            .for_each(|(t, _)| t.extend());
+        self.timers_changed = true;
+
+        self.cancel_fade()
+    }
+
+    fn release_timers(&mut self, book: &Book) -> Result<(), UAPlayerError> {
+        let mut progress = self.progress.get(book);
+        if progress.timers.is_empty() {
+            return Ok(());
+        }
+        progress.timers.clear();
+        self.progress.update(book, progress);
+        self.progress.save()
+    }
+
+    fn quit(
+        &mut self,
+        book: &Book,
+        mc_os_s: &crossbeam::channel::Sender<LibMpvEventMessage>,
+    ) -> Result<(), UAPlayerError> {
+        mc_os_s.send(LibMpvEventMessage::Quit)?;
+        self.save_progress(book)?;
+
+        self.mpv.command("quit", &["0"])?;
+
+        Ok(())
+    }
+
+    fn start_fade(&mut self, duration: f64) -> Result<(), libmpv2::Error> {
+        if self.fade.is_some() {
+            return Ok(());
+        }
+        self.fade = Some(Fade {
+            start: std::time::Instant::now(),
+            duration,
+            volume: self.mpv.get_property::<i64>("volume")?,
+            position: self.mpv.get_property::<f64>("time-pos/full").ok(),
+        });
+
+        Ok(())
+    }
+
+    fn update_fade(&self) -> Result<(), libmpv2::Error> {
+        let Some(fade) = self.fade else {
+            return Ok(());
+        };
+        let left = 1.0 - fade.start.elapsed().as_secs_f64() / fade.duration.max(1.0);
+        let volume = (fade.volume as f64 * left.max(0.0)).round() as i64;
+        if self.mpv.get_property::<i64>("volume")? != volume {
+            self.mpv.set_property("volume", volume)?;
+        }
+
+        Ok(())
+    }
+
+    fn cancel_fade(&mut self) -> Result<(), libmpv2::Error> {
+        if let Some(fade) = self.fade.take().or(self.faded.take()) {
+            self.mpv.set_property("volume", fade.volume)?;
+        }
+
+        Ok(())
+    }
+
+    // Restores the volume after a sleep timer faded out and returns to where the fade began.
+    fn restore_fade(&mut self) -> Result<bool, libmpv2::Error> {
+        let Some(fade) = self.faded.take() else {
+            return Ok(false);
+        };
+        self.mpv.set_property("volume", fade.volume)?;
+        let Some(position) = fade
+            .position
+            .filter(|_| self.config.sleep_timer.still_listening)
+        else {
+            return Ok(false);
+        };
+        self.mpv
+            .command("seek", &[&position.to_string(), "absolute"])?;
+
+        Ok(true)
+    }
+
+    fn finish_book(&mut self, book: &Book) -> Result<(), UAPlayerError> {
+        let mut progress = self.progress.get(book);
+        progress.set_book_position(&BookPosition::default());
+        progress.finished = true;
+        progress.last_played = chrono::Utc::now().timestamp();
+        progress.timers.clear();
+        self.progress.update(book, progress);
+        self.progress.save()?;
+        record_recent(&book.path, 0.0);
+
+        Ok(())
+    }
+
+    fn next_queued(&mut self) -> Result<Option<Book>, UAPlayerError> {
+        let mut next = None;
+        while next.is_none() && !self.queue.is_empty() {
+            let path = self.queue.remove(0);
+            match Book::open(std::path::Path::new(&path)) {
+                Ok(book) => next = Some(book),
+                Err(err) => log::error!("LibMpv: skipping queued {path}: {err:?}"),
+            }
+        }
+        save_queue(&self.queue)?;
+
+        Ok(next)
+    }
+
+    fn dequeue(&mut self, path: &str) -> Result<bool, UAPlayerError> {
+        let len = self.queue.len();
+        self.queue.retain(|p| p != path);
+        if self.queue.len() == len {
+            return Ok(false);
+        }
+        save_queue(&self.queue)?;
+
+        Ok(true)
+    }
+
+    fn book_position(&self, book: &Book, time: f64) -> BookPosition {
+        let part = self
+            .part_starts
+            .iter()
+            .rposition(|&start| start <= time)
+            .map(|i| (book.part_name(i), time - self.part_starts[i]));
+
+        BookPosition { time, part }
+    }
+
     pub fn run(
         &mut self,
         mut mpv_client: libmpv2::Mpv,
-        path: &str,
-        time: f64,
+        mut book: Book,
         tui_s: crossbeam::channel::Sender<LibMpvEventMessage>,
         mc_os_s: crossbeam::channel::Sender<LibMpvEventMessage>,
         libmpv_r: crossbeam::channel::Receiver<LibMpvMessage>,
     ) -> Result<(), UAPlayerError> {
-        self.load_file(path)?;
+        let mut position = self.open_book(&book)?;
+        tui_s.send(LibMpvEventMessage::QueueUpdate(self.queue.clone()))?;
+        tui_s.send(LibMpvEventMessage::SkipSilenceUpdate(self.skip_silence))?;
+        tui_s.send(LibMpvEventMessage::ChannelsUpdate((
+            self.mono,
+            self.balance,
+        )))?;
+        tui_s.send(LibMpvEventMessage::NormalizationUpdate((
+            self.normalization,
+            self.compressor,
+        )))?;
+        tui_s.send(LibMpvEventMessage::LoopUpdate(self.ab_loop))?;
 
+        let mut timers_update = std::time::Instant::now();
         loop {
+            let mut timer_action = None;
             let ev = mpv_client
                 .wait_event(0.016)
                 .unwrap_or(Err(libmpv2::Error::Null));
@@ -123,9 +1072,18 @@ impl LibMpvHandler {
                         ..
                     } => {
                         if pause {
+                            if let Some(fade) = self.fade.take() {
+                                self.faded = Some(fade);
+                            }
+                            self.paused_at = Some(std::time::Instant::now());
+                            self.save_progress(&book)?;
                             tui_s.send(LibMpvEventMessage::PlaybackPause)?;
                             mc_os_s.send(LibMpvEventMessage::PlaybackPause)?;
                         } else {
+                            if self.restore_fade()? {
+                                self.paused_at = None;
+                            }
+                            self.rewind_after_pause()?;
                             tui_s.send(LibMpvEventMessage::PlaybackResume)?;
                             mc_os_s.send(LibMpvEventMessage::PlaybackResume)?;
                         }
@@ -138,40 +1096,73 @@ impl LibMpvHandler {
                         tui_s.send(LibMpvEventMessage::VolumeUpdate(volume))?;
                         mc_os_s.send(LibMpvEventMessage::VolumeUpdate(volume))?;
                     }
+                    libmpv2::events::Event::PropertyChange {
+                        name: "speed",
+                        change: libmpv2::events::PropertyData::Double(speed),
+                        ..
+                    } => {
+                        tui_s.send(LibMpvEventMessage::SpeedUpdate(speed))?;
+                        mc_os_s.send(LibMpvEventMessage::SpeedUpdate(speed))?;
+                        // Playback time is extrapolated from the speed, so resync it.
+                        if let Ok(time_pos) = self.mpv.get_property::<f64>("time-pos/full") {
+                            tui_s.send(LibMpvEventMessage::PositionUpdate(time_pos))?;
+                            mc_os_s.send(LibMpvEventMessage::PositionUpdate(time_pos))?;
+                        }
+                    }
                     libmpv2::events::Event::PropertyChange {
                         name: "chapter",
                         change: libmpv2::events::PropertyData::Int64(i),
                         ..
                     } => {
-                        if i >= 0 {
-                            if let Some(chapter) = self.chapters.get(i as usize) {
-                                let chapter = chapter.title.clone();
-                                tui_s.send(LibMpvEventMessage::ChapterUpdate((
-                                    chapter.clone(),
-                                    i as usize,
-                                )))?;
-                                mc_os_s.send(LibMpvEventMessage::ChapterUpdate((
-                                    chapter, i as usize,
-                                )))?;
+                        if i >= 0
+                            && !self.external_chapters
+                            && let Some(chapter) = self.chapters.get(i as usize)
+                        {
+                            let chapter = chapter.title.clone();
+                            tui_s.send(LibMpvEventMessage::ChapterUpdate((
+                                chapter.clone(),
+                                i as usize,
+                            )))?;
+                            mc_os_s
+                                .send(LibMpvEventMessage::ChapterUpdate((chapter, i as usize)))?;
+                            timer_action = self.chapter_timers_reached(i as usize);
+                            if self.follow_repeat_chapter(i as usize)? {
+                                tui_s.send(LibMpvEventMessage::LoopUpdate(self.ab_loop))?;
                             }
                         }
                     }
                     libmpv2::events::Event::Seek => {
+                        self.time_saved_sample = None;
                         let time_pos = self.mpv.get_property::<f64>("time-pos/full")?;
                         tui_s.send(LibMpvEventMessage::PositionUpdate(time_pos))?;
                         mc_os_s.send(LibMpvEventMessage::PositionUpdate(time_pos))?;
                     }
+                    libmpv2::events::Event::EndFile(libmpv2::mpv_end_file_reason::Eof) => {
+                        self.finish_book(&book)?;
+                        // A chapter timer reaching the end of the book stops the queue too.
+                        timer_action = self.chapter_timers_reached(usize::MAX);
+                        if timer_action.is_some() {
+                            self.cancel_fade()?;
+                        } else if let Some(next) = self.next_queued()? {
+                            book = next;
+                            position = self.open_book(&book)?;
+                            tui_s.send(LibMpvEventMessage::QueueUpdate(self.queue.clone()))?;
+                        }
+                    }
                     libmpv2::events::Event::FileLoaded => {
-                        let media_title = self
-                            .mpv
-                            .get_property::<libmpv2::MpvStr>("metadata/by-key/title")?
-                            .to_string();
+                        let media_title = media_title(&self.mpv, &book);
                         let duration = self.mpv.get_property::<f64>("duration/full")?;
+                        self.fech_chapters(&book)?;
+                        let time = self.resolve_position(&book, &position);
                         self.mpv.command("seek", &[&time.to_string(), "absolute"])?;
-                        self.fech_chapters()?;
                         let chapter = {
                             if self.chapters.len() > 0 {
-                                let chapter_num = self.mpv.get_property::<i64>("chapter")?;
+                                let chapter_num = if self.external_chapters {
+                                    self.current_chapter = self.chapter_at(time);
+                                    self.current_chapter.map_or(-1, |i| i as i64)
+                                } else {
+                                    self.mpv.get_property::<i64>("chapter")?
+                                };
                                 if let Some(chapter) = self.chapters.get(chapter_num as usize) {
                                     (Some(chapter.title.clone()), chapter_num as usize)
                                 } else {
@@ -182,23 +1173,25 @@ impl LibMpvHandler {
                             }
                         };
                         let volume = self.mpv.get_property::<i64>("volume")?;
-                        let artist = self
-                            .mpv
-                            .get_property::<libmpv2::MpvStr>("metadata/by-key/artist")
-                            .map(|s| Some(s.to_string()))
-                            .unwrap_or_else(|_| None);
-                        let album = self
-                            .mpv
-                            .get_property::<libmpv2::MpvStr>("metadata/by-key/album")
-                            .map(|s| Some(s.to_string()))
-                            .unwrap_or_else(|_| None);
+                        let speed = self.mpv.get_property::<f64>("speed")?;
+                        let artist = metadata(&self.mpv, "artist");
+                        let album = metadata(&self.mpv, "album");
 
+                        tui_s.send(LibMpvEventMessage::LoopUpdate(self.ab_loop))?;
+                        tui_s.send(LibMpvEventMessage::EqUpdate(self.eq.clone()))?;
+                        tui_s.send(LibMpvEventMessage::BookmarksUpdate(
+                            self.progress.get(&book).bookmarks,
+                        ))?;
+                        tui_s.send(LibMpvEventMessage::TranscriptUpdate(
+                            load_transcript(std::path::Path::new(&book.path)).unwrap_or_default(),
+                        ))?;
                         tui_s.send(LibMpvEventMessage::FileLoaded(FileLoadedData {
                             media_title: media_title.clone(),
                             artist: artist.clone(),
                             album: album.clone(),
                             duration,
                             volume,
+                            speed,
                             chapter: chapter.clone(),
                             chapters: self.chapters.clone(),
                         }))?;
@@ -208,6 +1201,7 @@ impl LibMpvHandler {
                             album,
                             duration,
                             volume,
+                            speed,
                             chapter,
                             chapters: vec![],
                         }))?;
@@ -219,22 +1213,56 @@ impl LibMpvHandler {
                 }
             }
 
+            if self.receive_silence_chapters(&book)? {
+                tui_s.send(LibMpvEventMessage::ChaptersUpdate(self.chapters.clone()))?;
+            }
+            if let Some(i) = self.update_external_chapter() {
+                let chapter = self.chapters[i].title.clone();
+                tui_s.send(LibMpvEventMessage::ChapterUpdate((chapter.clone(), i)))?;
+                mc_os_s.send(LibMpvEventMessage::ChapterUpdate((chapter, i)))?;
+                timer_action = timer_action.or(self.chapter_timers_reached(i));
+                if self.follow_repeat_chapter(i)? {
+                    tui_s.send(LibMpvEventMessage::LoopUpdate(self.ab_loop))?;
+                }
+            }
+
+            timer_action = timer_action.or(self.tick_timers()?);
+            match timer_action {
+                Some(TimerAction::Pause) => self.mpv.set_property("pause", true)?,
+                Some(TimerAction::Quit) => {
+                    tui_s.send(LibMpvEventMessage::Quit)?;
+                    self.quit(&book, &mc_os_s)?;
+                    break;
+                }
+                None => (),
+            }
+            if self.timers_changed
+                || (!self.timers.is_empty() && timers_update.elapsed().as_secs() >= 1)
+            {
+                self.timers_changed = false;
+                timers_update = std::time::Instant::now();
+                tui_s.send(LibMpvEventMessage::TimersUpdate((
+                    self.timer_statuses(),
+                    self.fade.is_some(),
+                )))?;
+            }
+            if let Some(status) = self.receive_clip_status() {
+                tui_s.send(LibMpvEventMessage::Status(status))?;
+            }
+            if self.history_changed {
+                self.history_changed = false;
+                tui_s.send(LibMpvEventMessage::HistoryUpdate(self.history.entries()))?;
+            }
+            self.update_fade()?;
+            if let Some(time_saved) = self.update_time_saved() {
+                tui_s.send(LibMpvEventMessage::TimeSavedUpdate(time_saved))?;
+            }
+
             if let Ok(msg) = libmpv_r.try_recv() {
                 log::debug!("LibMpv::LibMpvMessage: {msg:?}");
                 match msg {
                     LibMpvMessage::Quit => {
-                        mc_os_s.send(LibMpvEventMessage::Quit)?;
-                        let diff = 5.0;
-                        let mut pos = self.mpv.get_property::<f64>("time-pos/full").unwrap_or(0.0);
-                        if pos > diff {
-                            pos -= diff;
-                        } else {
-                            pos = 0.0;
-                        }
-                        let mut file = std::fs::File::create(format!("{path}.txt"))?;
-                        file.write_all(pos.to_string().as_bytes())?;
-
-                        self.mpv.command("quit", &["0"])?;
+                        self.quit(&book, &mc_os_s)?;
                         break;
                     }
                     LibMpvMessage::UpdateVolume(vol) => {
@@ -247,27 +1275,91 @@ impl LibMpvHandler {
                         let volume = vol.clamp(0, 200);
                         self.mpv.set_property("volume", volume)?;
                     }
+                    LibMpvMessage::UpdateSpeed(offset) => {
+                        let speed = self.mpv.get_property::<f64>("speed")?;
+                        self.set_speed(&book, speed + offset)?;
+                    }
+                    LibMpvMessage::SetSpeed(speed) => {
+                        self.set_speed(&book, speed)?;
+                    }
                     LibMpvMessage::UpdatePosition(offset) => {
+                        self.record_jump();
                         self.mpv.command("seek", &[&offset.to_string()])?;
                     }
                     LibMpvMessage::SetPosition(pos) => {
+                        self.record_jump();
                         self.mpv.command("seek", &[&pos.to_string(), "absolute"])?;
                     }
+                    LibMpvMessage::AddBookmark(note) => {
+                        self.add_bookmark(&book, note)?;
+                        tui_s.send(LibMpvEventMessage::BookmarksUpdate(
+                            self.progress.get(&book).bookmarks,
+                        ))?;
+                    }
+                    LibMpvMessage::DeleteBookmark(i) => {
+                        if self.delete_bookmark(&book, i)? {
+                            tui_s.send(LibMpvEventMessage::BookmarksUpdate(
+                                self.progress.get(&book).bookmarks,
+                            ))?;
+                        } else {
+                            tui_s.send(LibMpvEventMessage::Error(format!(
+                                "Error: no bookmark {}",
+                                i + 1
+                            )))?;
+                        }
+                    }
+                    LibMpvMessage::ExportClip((start, end, path)) => {
+                        let status = match self.start_clip_export(&book, start, end, path) {
+                            Ok(output) => format!("Exporting clip to {output}"),
+                            Err(err) => format!("Error: {err}"),
+                        };
+                        tui_s.send(LibMpvEventMessage::Status(status))?;
+                    }
+                    LibMpvMessage::UndoSeek => {
+                        self.undo_jump(false)?;
+                    }
+                    LibMpvMessage::RedoSeek => {
+                        self.undo_jump(true)?;
+                    }
                     LibMpvMessage::PlayPause => {
                         self.mpv.command("cycle", &["pause"])?;
                     }
+                    LibMpvMessage::PrevChapter if self.external_chapters => {
+                        let time = self.mpv.get_property::<f64>("time-pos/full")?;
+                        if let Some(start) = self
+                            .chapter_at(time)
+                            .and_then(|i| i.checked_sub(1))
+                            .and_then(|i| self.chapters.get(i))
+                            .map(|c| c.time)
+                        {
+                            self.record_jump();
+                            self.mpv
+                                .command("seek", &[&start.to_string(), "absolute"])?;
+                        }
+                    }
                     LibMpvMessage::PrevChapter => {
                         if self.chapters.len() > 0 {
                             let chapter = self.mpv.get_property::<i64>("chapter")? - 1;
                             if chapter >= 0 {
+                                self.record_jump();
                                 self.mpv.set_property("chapter", chapter)?;
                             }
                         }
                     }
+                    LibMpvMessage::NextChapter if self.external_chapters => {
+                        let time = self.mpv.get_property::<f64>("time-pos/full")?;
+                        let next = self.chapter_at(time).map_or(0, |i| i + 1);
+                        if let Some(start) = self.chapters.get(next).map(|c| c.time) {
+                            self.record_jump();
+                            self.mpv
+                                .command("seek", &[&start.to_string(), "absolute"])?;
+                        }
+                    }
                     LibMpvMessage::NextChapter => {
                         if self.chapters.len() > 0 {
                             let chapter = self.mpv.get_property::<i64>("chapter")? + 1;
                             if chapter < (self.chapters.len() as i64) {
+                                self.record_jump();
                                 self.mpv.set_property("chapter", chapter)?;
                             }
                         }
@@ -278,6 +1370,141 @@ impl LibMpvHandler {
                     LibMpvMessage::Pause => {
                         self.mpv.set_property("pause", true)?;
                     }
+                    LibMpvMessage::LoadBook(path) => {
+                        match Book::open(std::path::Path::new(&path)) {
+                            Ok(new_book) => {
+                                self.save_progress(&book)?;
+                                self.release_timers(&book)?;
+                                book = new_book;
+                                position = self.open_book(&book)?;
+                                if self.dequeue(&book.path)? {
+                                    tui_s.send(LibMpvEventMessage::QueueUpdate(
+                                        self.queue.clone(),
+                                    ))?;
+                                }
+                            }
+                            Err(err) => {
+                                log::error!("LibMpv::LoadBook: {path}: {err:?}");
+                                let reason = match err {
+                                    UAPlayerError::InvalidFile(reason) => reason,
+                                    _ => format!("cannot open {path}"),
+                                };
+                                tui_s
+                                    .send(LibMpvEventMessage::Error(format!("Error: {reason}")))?;
+                            }
+                        }
+                    }
+                    LibMpvMessage::QueueAdd(path) => {
+                        match Book::open(std::path::Path::new(&path)) {
+                            Ok(new_book) => {
+                                self.queue.retain(|p| p != &new_book.path);
+                                self.queue.push(new_book.path);
+                                save_queue(&self.queue)?;
+                                tui_s.send(LibMpvEventMessage::QueueUpdate(self.queue.clone()))?;
+                            }
+                            Err(err) => {
+                                log::error!("LibMpv::QueueAdd: {path}: {err:?}");
+                                let reason = match err {
+                                    UAPlayerError::InvalidFile(reason) => reason,
+                                    _ => format!("cannot open {path}"),
+                                };
+                                tui_s
+                                    .send(LibMpvEventMessage::Error(format!("Error: {reason}")))?;
+                            }
+                        }
+                    }
+                    LibMpvMessage::AddTimer(request) => {
+                        if let Err(err) = self.add_timer(request) {
+                            tui_s.send(LibMpvEventMessage::Error(format!("Error: {err}")))?;
+                        }
+                    }
+                    LibMpvMessage::CancelTimer(name) => {
+                        if let Err(err) = self.cancel_timer(name) {
+                            tui_s.send(LibMpvEventMessage::Error(format!("Error: {err}")))?;
+                        }
+                        self.cancel_fade()?;
+                    }
+                    LibMpvMessage::ExtendTimers => {
+                        self.extend_timers()?;
+                    }
+                    LibMpvMessage::LoopA => {
+                        self.set_loop_point(false)?;
+                        tui_s.send(LibMpvEventMessage::LoopUpdate(self.ab_loop))?;
+                    }
+                    LibMpvMessage::LoopB => {
+                        self.set_loop_point(true)?;
+                        tui_s.send(LibMpvEventMessage::LoopUpdate(self.ab_loop))?;
+                    }
+                    LibMpvMessage::LoopClear => {
+                        self.ab_loop.repeat_chapter = false;
+                        self.set_ab_loop(None, None)?;
+                        tui_s.send(LibMpvEventMessage::LoopUpdate(self.ab_loop))?;
+                    }
+                    LibMpvMessage::RepeatChapter(enabled) => {
+                        let enabled = enabled.unwrap_or(!self.ab_loop.repeat_chapter);
+                        self.set_repeat_chapter(enabled)?;
+                        tui_s.send(LibMpvEventMessage::LoopUpdate(self.ab_loop))?;
+                    }
+                    LibMpvMessage::SkipSilence(enabled) => {
+                        self.set_skip_silence(enabled)?;
+                        tui_s.send(LibMpvEventMessage::SkipSilenceUpdate(enabled))?;
+                    }
+                    LibMpvMessage::Normalization(mode) => {
+                        self.set_normalization(mode)?;
+                        tui_s.send(LibMpvEventMessage::NormalizationUpdate((
+                            self.normalization,
+                            self.compressor,
+                        )))?;
+                    }
+                    LibMpvMessage::Compressor(enabled) => {
+                        self.set_compressor(enabled)?;
+                        tui_s.send(LibMpvEventMessage::NormalizationUpdate((
+                            self.normalization,
+                            self.compressor,
+                        )))?;
+                    }
+                    LibMpvMessage::Eq(preset) => {
+                        if !self.set_book_eq(&book, &preset)? {
+                            tui_s.send(LibMpvEventMessage::Error(format!(
+                                "Error: unknown eq preset {preset}"
+                            )))?;
+                        }
+                        tui_s.send(LibMpvEventMessage::EqUpdate(self.eq.clone()))?;
+                    }
+                    LibMpvMessage::ListAudioDevices => {
+                        let device = self.mpv.get_property::<String>("audio-device")?;
+                        tui_s.send(LibMpvEventMessage::AudioDevicesUpdate((
+                            self.audio_devices(),
+                            device,
+                        )))?;
+                    }
+                    LibMpvMessage::SetAudioDevice(name) => {
+                        // mpv reopens the output in place, playback continues where it was.
+                        self.mpv.set_property("audio-device", name.as_str())?;
+                        tui_s.send(LibMpvEventMessage::AudioDevicesUpdate((
+                            self.audio_devices(),
+                            name,
+                        )))?;
+                    }
+                    LibMpvMessage::Mono(enabled) => {
+                        self.set_channels(enabled, self.balance)?;
+                        tui_s.send(LibMpvEventMessage::ChannelsUpdate((
+                            self.mono,
+                            self.balance,
+                        )))?;
+                    }
+                    LibMpvMessage::Balance(balance) => {
+                        self.set_channels(self.mono, balance)?;
+                        tui_s.send(LibMpvEventMessage::ChannelsUpdate((
+                            self.mono,
+                            self.balance,
+                        )))?;
+                    }
+                    LibMpvMessage::QueueClear => {
+                        self.queue.clear();
+                        save_queue(&self.queue)?;
+                        tui_s.send(LibMpvEventMessage::QueueUpdate(vec![]))?;
+                    }
                 }
             }
         }
@@ -286,3 +1513,111 @@ impl LibMpvHandler {
         Ok(())
     }
 }
+
+fn metadata(mpv: &libmpv2::Mpv, key: &str) -> Option<String> {
+    mpv.get_property::<libmpv2::MpvStr>(&format!("metadata/by-key/{key}"))
+        .map(|s| s.to_string())
+        .ok()
+}
+
+fn media_title(mpv: &libmpv2::Mpv, book: &Book) -> String {
+    if book.is_multipart() {
+        metadata(mpv, "album").unwrap_or_else(|| book.title())
+    } else {
+        metadata(mpv, "title")
+            .or_else(|| {
+                mpv.get_property::<libmpv2::MpvStr>("media-title")
+                    .map(|s| s.to_string())
+                    .ok()
+            })
+            .unwrap_or_else(|| book.title())
+    }
+}
+
+pub struct BookChapters {
+    pub chapters: Vec<Chapter>,
+    pub part_starts: Vec<f64>,
+    pub external: bool,
+}
+
+// Chapters of the loaded book from its parts, its CUE sheet or the file itself.
+pub fn read_chapters(mpv: &libmpv2::Mpv, book: &Book) -> Result<BookChapters, libmpv2::Error> {
+    let chapters = mpv.get_property::<libmpv2::MpvStr>("chapter-list")?;
+    let chapters: Vec<Chapter> = serde_json::from_str(chapters.trim()).unwrap_or(vec![]);
+
+    if book.is_multipart() {
+        // Every EDL segment starts with a chapter titled with the part's file name (see
+        // Book::mpv_path), possibly interleaved with the chapters embedded in the files.
+        let mut chapters_iter = chapters.iter();
+        let part_chapters: Vec<Chapter> = book
+            .parts
+            .iter()
+            .map_while(|part| {
+                let file_name = part.file_name();
+                chapters_iter
+                    .find(|c| c.title == file_name)
+                    .map(|c| Chapter {
+                        title: part.display_title(),
+                        time: c.time,
+                    })
+            })
+            .collect();
+        if part_chapters.len() == book.parts.len() {
+            return Ok(BookChapters {
+                part_starts: part_chapters.iter().map(|c| c.time as f64).collect(),
+                chapters: part_chapters,
+                external: true,
+            });
+        }
+        log::error!("LibMpv: chapters of {} do not match its parts", book.path);
+    } else if let Some(cue_chapters) =
+        crate::cue::load_cue_chapters(std::path::Path::new(&book.path))
+    {
+        return Ok(BookChapters {
+            chapters: cue_chapters,
+            part_starts: vec![],
+            external: true,
+        });
+    }
+
+    Ok(BookChapters {
+        chapters,
+        part_starts: vec![],
+        external: false,
+    })
+}
+
+// Loads the book paused into a standalone mpv instance without audio output.
+pub fn open_standalone(book: &Book) -> Result<libmpv2::Mpv, libmpv2::Error> {
+    let mut mpv = libmpv2::Mpv::with_initializer(|init| {
+        init.set_property("vo", "null")?;
+        init.set_property("ao", "null")?;
+        init.set_property("pause", true)?;
+        Ok(())
+    })?;
+    mpv.disable_deprecated_events()?;
+    mpv.command("loadfile", &[&book.mpv_path(), "replace"])?;
+
+    loop {
+        match mpv.wait_event(10.0) {
+            Some(Ok(libmpv2::events::Event::FileLoaded)) => break,
+            Some(Ok(libmpv2::events::Event::EndFile(_))) | None => {
+                return Err(libmpv2::Error::Null);
+            }
+            Some(Err(err)) => return Err(err),
+            Some(Ok(_)) => (),
+        }
+    }
+
+    Ok(mpv)
+}
+
+pub fn probe(book: &Book) -> Result<ProbeData, libmpv2::Error> {
+    let mpv = open_standalone(book)?;
+
+    Ok(ProbeData {
+        title: media_title(&mpv, book),
+        artist: metadata(&mpv, "artist"),
+        duration: mpv.get_property::<f64>("duration/full").unwrap_or(0.0),
+    })
+}
@@ -0,0 +1,123 @@
+use crate::book::Book;
+use crate::format::{self, AudioFormat};
+use crate::is_audiofile;
+use crate::progress::ProgressStore;
+
+#[derive(Debug, Clone)]
+pub struct LibraryEntry {
+    pub path: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub duration: f64,
+    pub position: f64,
+    pub finished: bool,
+}
+
+impl LibraryEntry {
+    pub fn percent_listened(&self) -> u64 {
+        if self.finished {
+            return 100;
+        }
+        if self.duration <= 0.0 {
+            return 0;
+        }
+
+        ((self.position / self.duration) * 100.0)
+            .clamp(0.0, 100.0)
+            .floor() as u64
+    }
+}
+
+pub fn scan(roots: &[String]) -> Vec<String> {
+    let mut books = vec![];
+    let mut visited = std::collections::HashSet::new();
+    for root in roots {
+        scan_dir(std::path::Path::new(root), &mut books, &mut visited);
+    }
+    books.sort();
+    books.dedup();
+
+    books
+}
+
+fn scan_dir(
+    dir: &std::path::Path,
+    books: &mut Vec<String>,
+    visited: &mut std::collections::HashSet<std::path::PathBuf>,
+) {
+    // Symlinked directories are followed, but each directory is only scanned once.
+    if !dir
+        .canonicalize()
+        .is_ok_and(|canonical| visited.insert(canonical))
+    {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        log::error!("Library: cannot read {}", dir.to_string_lossy());
+        return;
+    };
+
+    let mut files = vec![];
+    let mut dirs = vec![];
+    for path in entries.filter_map(|e| e.ok().map(|e| e.path())) {
+        if path.is_dir() {
+            dirs.push(path);
+        } else if path.is_file() && is_audiofile(&path) {
+            files.push(path);
+        }
+    }
+
+    // A directory of several parts is a single book, unless it holds standalone m4b books.
+    let standalone_files = files
+        .iter()
+        .any(|f| format::detect(f) == Ok(AudioFormat::M4b));
+    if files.len() > 1 && !standalone_files {
+        books.push(dir.to_string_lossy().to_string());
+    } else {
+        books.extend(files.iter().map(|f| f.to_string_lossy().to_string()));
+    }
+
+    for dir in dirs {
+        scan_dir(&dir, books, visited);
+    }
+}
+
+pub fn load(roots: &[String], library_s: crossbeam::channel::Sender<LibraryEntry>) {
+    let mut progress = ProgressStore::load();
+    for path in scan(roots) {
+        let Ok(book) = Book::open(std::path::Path::new(&path)) else {
+            continue;
+        };
+        let probe = match crate::libmpv_handler::probe(&book) {
+            Ok(probe) => probe,
+            Err(err) => {
+                log::error!("Library: cannot probe {}: {err:?}", book.path);
+                continue;
+            }
+        };
+
+        let progress = progress.get(&book);
+        let entry = LibraryEntry {
+            path: book.path,
+            title: probe.title,
+            author: probe.artist,
+            duration: probe.duration,
+            position: progress.position,
+            finished: progress.finished,
+        };
+        if library_s.send(entry).is_err() {
+            break;
+        }
+    }
+}
+
+// Books are only scanned once, their progress is reread whenever the library is shown.
+pub fn refresh_progress(library: &mut [LibraryEntry]) {
+    let progress = ProgressStore::load();
+    for entry in library {
+        if let Some(book_progress) = progress.books.get(&entry.path) {
+            entry.position = book_progress.position;
+            entry.finished = book_progress.finished;
+        }
+    }
+}
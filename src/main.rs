@@ -1,7 +1,9 @@
 use unplugged_audiobook_player::{
-    ProgramOption,
+    ProgramOption, UAPlayerError,
+    book::Book,
+    config::Config,
     libmpv_handler::{LibMpvEventMessage, LibMpvMessage},
-    print_help, process_args, save_path_to_config,
+    print_help, process_args,
 };
 
 fn main() {
@@ -15,8 +17,8 @@ fn main() {
                 unplugged_audiobook_player::UAPlayerError::InvalidOptionsStructure => {
                     eprintln!("Invalid input")
                 }
-                unplugged_audiobook_player::UAPlayerError::InvalidFile => {
-                    eprintln!("Provide valid audiobook file (.m4b/.mp3)")
+                unplugged_audiobook_player::UAPlayerError::InvalidFile(reason) => {
+                    eprintln!("Provide valid audiobook file or directory: {reason}")
                 }
                 _ => panic!("{:?}", err),
             }
@@ -44,6 +46,51 @@ fn main() {
         log::debug!("Args: {:?}", std::env::args());
     }
 
+    let mut config = Config::load()
+        .map_err(|err| {
+            match err {
+                UAPlayerError::InvalidConfig(err) => eprintln!("Invalid config file {err}"),
+                _ => panic!("{:?}", err),
+            }
+            std::process::exit(-1);
+        })
+        .unwrap();
+    if let Some(device) = options.iter().find_map(|o| match o {
+        ProgramOption::AudioDevice(device) => Some(device),
+        _ => None,
+    }) {
+        config.audio.device = Some(device.clone());
+    }
+    let config = &config;
+
+    for option in &options {
+        match option {
+            ProgramOption::ExportSplit(dir) => {
+                let book = Book::open(std::path::Path::new(book_path(&options))).unwrap();
+                match unplugged_audiobook_player::export::split_book(&book, config, dir) {
+                    Ok(outputs) => outputs.iter().for_each(|o| println!("Exported {o}")),
+                    Err(err) => {
+                        eprintln!("Cannot split {}: {err:?}", book.path);
+                        std::process::exit(-1);
+                    }
+                }
+                std::process::exit(0);
+            }
+            ProgramOption::ExportMerge(file) => {
+                let book = Book::open(std::path::Path::new(book_path(&options))).unwrap();
+                match unplugged_audiobook_player::export::merge_book(&book, file) {
+                    Ok(output) => println!("Exported {output}"),
+                    Err(err) => {
+                        eprintln!("Cannot merge {}: {err:?}", book.path);
+                        std::process::exit(-1);
+                    }
+                }
+                std::process::exit(0);
+            }
+            _ => (),
+        }
+    }
+
     let volume = if let Some(vol) = options.iter().find_map(|o| match o {
         ProgramOption::Volume(vol) => Some(*vol),
         _ => None,
@@ -53,21 +100,7 @@ fn main() {
         100
     };
 
-    let file_path = options
-        .iter()
-        .find_map(|o| match o {
-            ProgramOption::PATH(path) => Some(path),
-            _ => None,
-        })
-        .unwrap();
-    save_path_to_config(file_path);
-
-    let time: f64 = if let Ok(str) = std::fs::read_to_string(format!("{file_path}.txt")) {
-        str.parse().unwrap()
-    } else {
-        0.0
-    };
-    log::debug!("Time: {time}");
+    let book = Book::open(std::path::Path::new(book_path(&options))).unwrap();
 
     let (tui_s, tui_r) = crossbeam::channel::unbounded();
     let (libmpv_s, libmpv_r) = crossbeam::channel::unbounded();
@@ -77,9 +110,11 @@ fn main() {
     let tui_s2 = tui_s.clone();
     let libmpv_s2 = libmpv_s.clone();
 
-    let mut mpv =
-        unplugged_audiobook_player::libmpv_handler::LibMpvHandler::initialize_libmpv(volume)
-            .unwrap();
+    let mut mpv = unplugged_audiobook_player::libmpv_handler::LibMpvHandler::initialize_libmpv(
+        volume,
+        config.clone(),
+    )
+    .unwrap();
     let mpv_client = mpv.create_client().unwrap();
     let mut mc_os_interface =
         unplugged_audiobook_player::mc_os_interface::MCOSInterface::new(libmpv_s.clone()).unwrap();
@@ -87,7 +122,7 @@ fn main() {
     crossbeam::scope(move |scope| {
         scope.spawn(move |_| {
             log::debug!("TUI: START");
-            unplugged_audiobook_player::tui::tui(libmpv_s.clone(), tui_r)
+            unplugged_audiobook_player::tui::tui(libmpv_s.clone(), tui_r, config)
                 .map_err(|err| {
                     log::error!("Tui: {:?}", err);
                     let _ = libmpv_s.send(LibMpvMessage::Quit);
@@ -99,21 +134,14 @@ fn main() {
         });
         scope.spawn(move |_| {
             log::debug!("MPV: START");
-            mpv.run(
-                mpv_client,
-                &file_path,
-                time,
-                tui_s.clone(),
-                mc_tui_s.clone(),
-                libmpv_r,
-            )
-            .map_err(|err| {
-                log::error!("MpvHandler: {:?}", err);
-                let _ = tui_s.send(LibMpvEventMessage::Quit);
-                let _ = mc_tui_s.send(LibMpvEventMessage::Quit);
-                err
-            })
-            .unwrap();
+            mpv.run(mpv_client, book, tui_s.clone(), mc_tui_s.clone(), libmpv_r)
+                .map_err(|err| {
+                    log::error!("MpvHandler: {:?}", err);
+                    let _ = tui_s.send(LibMpvEventMessage::Quit);
+                    let _ = mc_tui_s.send(LibMpvEventMessage::Quit);
+                    err
+                })
+                .unwrap();
             log::debug!("MPV: END");
         });
         scope.spawn(move |_| {
@@ -136,3 +164,13 @@ fn main() {
         log_send.send_quit_signal();
     }
 }
+
+fn book_path(options: &[ProgramOption]) -> &str {
+    options
+        .iter()
+        .find_map(|o| match o {
+            ProgramOption::PATH(path) => Some(path.as_str()),
+            _ => None,
+        })
+        .unwrap()
+}
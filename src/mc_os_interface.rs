@@ -90,6 +90,7 @@ impl MCOSInterface {
         let mut playback_start_offset = 0.0;
         let mut playback_paused = true;
         let mut playback_ready = false;
+        let mut playback_speed = 1.0;
 
         let mut update_playback_timer = std::time::SystemTime::now();
 
@@ -109,6 +110,7 @@ impl MCOSInterface {
                     LibMpvEventMessage::FileLoaded(data) => {
                         playback_start = std::time::SystemTime::now();
                         playback_start_offset = 0.0;
+                        playback_speed = data.speed;
 
                         self.media_controller
                             .set_metadata(souvlaki::MediaMetadata {
@@ -122,7 +124,8 @@ impl MCOSInterface {
                         self.media_controller
                             .set_playback(souvlaki::MediaPlayback::Paused { progress: None })?;
 
-                        playback_start_offset += playback_start.elapsed()?.as_secs_f64();
+                        playback_start_offset +=
+                            playback_start.elapsed()?.as_secs_f64() * playback_speed;
                         playback_paused = true;
                     }
                     LibMpvEventMessage::PlaybackResume => {
@@ -136,7 +139,27 @@ impl MCOSInterface {
                         #[cfg(target_os = "linux")]
                         self.media_controller.set_volume((vol as f64) / 100.0)?;
                     }
+                    LibMpvEventMessage::SpeedUpdate(speed) => {
+                        // souvlaki 0.8 hardcodes MPRIS Rate to 1.0 and has no SetRate event, so
+                        // the speed only reaches the media controls through the positions.
+                        playback_speed = speed;
+                    }
+                    LibMpvEventMessage::SkipSilenceUpdate(_) => (),
+                    LibMpvEventMessage::NormalizationUpdate(_) => (),
+                    LibMpvEventMessage::EqUpdate(_) => (),
+                    LibMpvEventMessage::AudioDevicesUpdate(_) => (),
+                    LibMpvEventMessage::ChannelsUpdate(_) => (),
+                    LibMpvEventMessage::TimeSavedUpdate(_) => (),
+                    LibMpvEventMessage::TimersUpdate(_) => (),
+                    LibMpvEventMessage::LoopUpdate(_) => (),
                     LibMpvEventMessage::ChapterUpdate(_) => (),
+                    LibMpvEventMessage::ChaptersUpdate(_) => (),
+                    LibMpvEventMessage::HistoryUpdate(_) => (),
+                    LibMpvEventMessage::BookmarksUpdate(_) => (),
+                    LibMpvEventMessage::TranscriptUpdate(_) => (),
+                    LibMpvEventMessage::Status(_) => (),
+                    LibMpvEventMessage::QueueUpdate(_) => (),
+                    LibMpvEventMessage::Error(_) => (),
                     LibMpvEventMessage::PositionUpdate(pos) => {
                         playback_start = std::time::SystemTime::now();
                         playback_start_offset = pos;
@@ -155,7 +178,8 @@ impl MCOSInterface {
                     } else if playback_paused {
                         playback_start_offset
                     } else {
-                        playback_start_offset + playback_start.elapsed()?.as_secs_f64()
+                        playback_start_offset
+                            + playback_start.elapsed()?.as_secs_f64() * playback_speed
                     }
                 };
 
@@ -0,0 +1,119 @@
+use crate::UAPlayerError;
+use crate::book::{Book, BookPosition};
+use crate::bookmarks::Bookmark;
+use crate::config::state_dir;
+use crate::history::HistoryEntry;
+use crate::timer::SleepTimer;
+
+const PROGRESS_VERSION: u32 = 1;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct BookProgress {
+    pub position: f64,
+    pub part: Option<String>,
+    pub part_offset: Option<f64>,
+    pub duration: f64,
+    pub last_played: i64,
+    pub finished: bool,
+    pub speed: Option<f64>,
+    pub eq: Option<String>,
+    pub timers: Vec<SleepTimer>,
+    pub history: Vec<HistoryEntry>,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+impl BookProgress {
+    pub fn book_position(&self) -> BookPosition {
+        BookPosition {
+            time: self.position,
+            part: self.part.clone().zip(self.part_offset),
+        }
+    }
+
+    pub fn set_book_position(&mut self, position: &BookPosition) {
+        self.position = position.time;
+        self.part = position.part.as_ref().map(|(name, _)| name.clone());
+        self.part_offset = position.part.as_ref().map(|(_, offset)| *offset);
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct ProgressStore {
+    pub version: u32,
+    pub books: std::collections::BTreeMap<String, BookProgress>,
+}
+
+impl Default for ProgressStore {
+    fn default() -> Self {
+        ProgressStore {
+            version: PROGRESS_VERSION,
+            books: std::collections::BTreeMap::new(),
+        }
+    }
+}
+
+impl ProgressStore {
+    fn file_path() -> Option<std::path::PathBuf> {
+        state_dir().map(|dir| dir.join("progress.json"))
+    }
+
+    pub fn load() -> Self {
+        let Some(path) = ProgressStore::file_path() else {
+            return ProgressStore::default();
+        };
+        let Ok(str) = std::fs::read_to_string(&path) else {
+            return ProgressStore::default();
+        };
+
+        match serde_json::from_str::<ProgressStore>(&str) {
+            Ok(store) if store.version <= PROGRESS_VERSION => store,
+            result => {
+                // Never overwrite a store this version cannot read.
+                log::error!("ProgressStore: cannot read {path:?}: {:?}", result.err());
+                let _ = std::fs::rename(&path, path.with_extension("json.bak"));
+                ProgressStore::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), UAPlayerError> {
+        let Some(path) = ProgressStore::file_path() else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(self).unwrap())?;
+        std::fs::rename(tmp_path, path)?;
+
+        Ok(())
+    }
+
+    pub fn get(&mut self, book: &Book) -> BookProgress {
+        if let Some(progress) = self.books.get(&book.path) {
+            return progress.clone();
+        }
+
+        let mut progress = BookProgress::default();
+        if let Some(position) = book.load_sidecar() {
+            log::debug!("ProgressStore: importing sidecar of {}", book.path);
+            progress.set_book_position(&position);
+            progress.last_played = std::fs::metadata(format!("{}.txt", book.path))
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0);
+            self.books.insert(book.path.clone(), progress.clone());
+        }
+
+        progress
+    }
+
+    pub fn update(&mut self, book: &Book, progress: BookProgress) {
+        self.books.insert(book.path.clone(), progress);
+    }
+}
@@ -0,0 +1,34 @@
+use crate::UAPlayerError;
+use crate::config::state_dir;
+
+fn queue_file_path() -> Option<std::path::PathBuf> {
+    state_dir().map(|dir| dir.join("queue.json"))
+}
+
+pub fn load_queue() -> Vec<String> {
+    queue_file_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|str| serde_json::from_str(&str).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_queue(queue: &[String]) -> Result<(), UAPlayerError> {
+    let Some(path) = queue_file_path() else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let json = serde_json::to_string_pretty(queue).unwrap();
+    std::fs::write(path, json)?;
+
+    Ok(())
+}
+
+pub fn queue_entry_name(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
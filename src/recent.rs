@@ -0,0 +1,138 @@
+use crate::UAPlayerError;
+use crate::config::state_dir;
+use std::io::Write;
+
+const RECENT_MAX_LEN: usize = 50;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct RecentEntry {
+    pub path: String,
+    pub last_played: i64,
+    pub position: f64,
+}
+
+impl RecentEntry {
+    pub fn name(&self) -> String {
+        std::path::Path::new(&self.path)
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.clone())
+    }
+
+    pub fn last_played_str(&self) -> String {
+        chrono::DateTime::from_timestamp(self.last_played, 0)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn recent_file_path() -> Option<std::path::PathBuf> {
+    state_dir().map(|dir| dir.join("recent.json"))
+}
+
+pub fn load_recent() -> Vec<RecentEntry> {
+    let Some(path) = recent_file_path() else {
+        return vec![];
+    };
+    if !path.is_file() {
+        return load_legacy_path()
+            .map(|path| {
+                vec![RecentEntry {
+                    path,
+                    last_played: 0,
+                    position: 0.0,
+                }]
+            })
+            .unwrap_or_default();
+    }
+
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|str| serde_json::from_str(&str).ok())
+        .unwrap_or_default()
+}
+
+pub fn record_recent(path: &str, position: f64) {
+    let mut entries = load_recent();
+    entries.retain(|e| e.path != path);
+    entries.insert(
+        0,
+        RecentEntry {
+            path: path.to_string(),
+            last_played: chrono::Utc::now().timestamp(),
+            position,
+        },
+    );
+    entries.truncate(RECENT_MAX_LEN);
+
+    if let Err(err) = save_recent(&entries) {
+        log::error!("Recent: {err:?}");
+    }
+}
+
+fn save_recent(entries: &[RecentEntry]) -> Result<(), UAPlayerError> {
+    let Some(path) = recent_file_path() else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let json = serde_json::to_string_pretty(entries).unwrap();
+    std::fs::write(path, json)?;
+
+    Ok(())
+}
+
+pub fn pick_recent(arg: &str) -> Result<String, UAPlayerError> {
+    let entries = load_recent();
+    if entries.is_empty() {
+        return Err(UAPlayerError::InvalidOption(arg.to_string()));
+    }
+
+    let choice = if let Some((_, n)) = arg.split_once('=') {
+        n.to_string()
+    } else {
+        for (i, entry) in entries.iter().enumerate() {
+            println!(
+                "{:>3}. {} ({})",
+                i + 1,
+                entry.name(),
+                entry.last_played_str()
+            );
+        }
+        print!("Select [1-{}]: ", entries.len());
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        line
+    };
+
+    choice
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|i| i.checked_sub(1))
+        .and_then(|i| entries.get(i))
+        .map(|e| e.path.clone())
+        .ok_or(UAPlayerError::InvalidOption(arg.to_string()))
+}
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn load_legacy_path() -> Option<String> {
+    let path = crate::config::config_dir()?.join("config");
+    if path.is_file() {
+        return std::fs::read_to_string(path).ok();
+    }
+
+    None
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn load_legacy_path() -> Option<String> {
+    std::fs::read_to_string("last.txt").ok()
+}
@@ -0,0 +1,117 @@
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TimerAction {
+    Pause,
+    Quit,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TimerClock {
+    Wall,
+    Listened,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimerLength {
+    Minutes(u64),
+    Chapters(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimerRequest {
+    pub name: String,
+    pub action: TimerAction,
+    pub length: TimerLength,
+    pub clock: Option<TimerClock>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum TimerCountdown {
+    Time {
+        clock: TimerClock,
+        duration: f64,
+        left: f64,
+    },
+    Chapter {
+        chapters: usize,
+        target: usize,
+    },
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct SleepTimer {
+    pub name: String,
+    pub action: TimerAction,
+    pub countdown: TimerCountdown,
+}
+
+#[derive(Debug, Clone)]
+pub struct TimerStatus {
+    pub name: String,
+    pub action: TimerAction,
+    pub kind: String,
+    pub left: f64,
+}
+
+impl SleepTimer {
+    pub fn tick(&mut self, elapsed: f64, playing: bool) {
+        if let TimerCountdown::Time { clock, left, .. } = &mut self.countdown
+            && (*clock == TimerClock::Wall || playing)
+        {
+            *left -= elapsed;
+        }
+    }
+
+    // Extends the timer by its original length.
+    pub fn extend(&mut self) {
+        match &mut self.countdown {
+            TimerCountdown::Time { duration, left, .. } => *left = left.max(0.0) + *duration,
+            TimerCountdown::Chapter { chapters, target } => *target += *chapters + 1,
+        }
+    }
+
+    pub fn kind(&self) -> String {
+        match &self.countdown {
+            TimerCountdown::Time {
+                clock: TimerClock::Wall,
+                ..
+            } => "wall".to_string(),
+            TimerCountdown::Time {
+                clock: TimerClock::Listened,
+                ..
+            } => "listened".to_string(),
+            TimerCountdown::Chapter { chapters: 0, .. } => "chapter".to_string(),
+            TimerCountdown::Chapter { chapters, .. } => format!("chapter+{chapters}"),
+        }
+    }
+}
+
+pub fn parse_timer_action(str: &str) -> Option<TimerAction> {
+    match str {
+        "pause" => Some(TimerAction::Pause),
+        "quit" => Some(TimerAction::Quit),
+        _ => None,
+    }
+}
+
+pub fn parse_timer_length(str: &str) -> Option<TimerLength> {
+    if let Some(chapters) = str.strip_prefix("chapter") {
+        let n = match chapters {
+            "" => 0,
+            n => n.strip_prefix('+')?.parse().ok()?,
+        };
+        return Some(TimerLength::Chapters(n));
+    }
+
+    str.parse().ok().map(TimerLength::Minutes)
+}
+
+pub fn parse_timer_clock(str: &str) -> Option<TimerClock> {
+    match str {
+        "wall" => Some(TimerClock::Wall),
+        "listened" => Some(TimerClock::Listened),
+        _ => None,
+    }
+}
@@ -0,0 +1,194 @@
+const TRANSCRIPT_EXTENSIONS: [&str; 3] = ["lrc", "srt", "vtt"];
+
+#[derive(Debug, Clone)]
+pub struct TranscriptLine {
+    pub time: f64,
+    pub text: String,
+}
+
+pub fn find_transcript(path: &std::path::Path) -> Option<std::path::PathBuf> {
+    TRANSCRIPT_EXTENSIONS
+        .iter()
+        .flat_map(|ext| {
+            [
+                path.with_extension(ext),
+                std::path::PathBuf::from(format!("{}.{ext}", path.to_string_lossy())),
+            ]
+        })
+        .find(|p| p.is_file())
+}
+
+pub fn load_transcript(path: &std::path::Path) -> Option<Vec<TranscriptLine>> {
+    let transcript_path = find_transcript(path)?;
+    let content = std::fs::read(&transcript_path).ok()?;
+    let content = String::from_utf8_lossy(&content);
+    let content = content.trim_start_matches('\u{feff}');
+
+    let mut lines = match transcript_path.extension().and_then(|e| e.to_str()) {
+        Some("lrc") => parse_lrc(content),
+        _ => parse_cues(content),
+    };
+    lines.sort_by(|a, b| a.time.total_cmp(&b.time));
+    if lines.is_empty() { None } else { Some(lines) }
+}
+
+// Index of the line being spoken at the given time.
+pub fn line_at(lines: &[TranscriptLine], time: f64) -> Option<usize> {
+    lines.iter().rposition(|l| l.time <= time)
+}
+
+pub fn parse_lrc(content: &str) -> Vec<TranscriptLine> {
+    let mut offset = 0.0;
+    let mut lines = vec![];
+    for line in content.lines() {
+        let mut rest = line.trim();
+        let mut times = vec![];
+        while let Some((tag, after)) = rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+            if let Some(ms) = tag.strip_prefix("offset:") {
+                // A positive offset makes the lines appear sooner.
+                offset = ms.trim().parse::<f64>().unwrap_or(0.0) / 1000.0;
+            } else if let Some(time) = parse_timestamp(tag) {
+                times.push(time);
+            }
+            rest = after;
+        }
+        let text = rest.trim();
+        if text.is_empty() {
+            continue;
+        }
+        lines.extend(times.into_iter().map(|time| TranscriptLine {
+            time,
+            text: text.to_string(),
+        }));
+    }
+
+    lines
+        .into_iter()
+        .map(|l| TranscriptLine {
+            time: (l.time - offset).max(0.0),
+            ..l
+        })
+        .collect()
+}
+
+// SRT and WebVTT share the cue layout, a timing line followed by the text lines.
+pub fn parse_cues(content: &str) -> Vec<TranscriptLine> {
+    let content = content.replace("\r\n", "\n");
+
+    content
+        .split("\n\n")
+        .filter_map(|block| {
+            let mut block_lines = block.lines().skip_while(|l| !l.contains("-->"));
+            let timing = block_lines.next()?;
+            let (start, _) = timing.split_once("-->")?;
+            let time = parse_timestamp(start.trim())?;
+            let text = block_lines
+                .map(|l| strip_tags(l.trim()))
+                .filter(|l| !l.is_empty())
+                .collect::<Vec<String>>()
+                .join(" ");
+
+            (!text.is_empty()).then_some(TranscriptLine { time, text })
+        })
+        .collect()
+}
+
+// Accepts [hh:]mm:ss with a fraction after '.' or ','.
+fn parse_timestamp(str: &str) -> Option<f64> {
+    let str = str.replace(',', ".");
+    let fields: Vec<&str> = str.split(':').collect();
+    let (hh, mm, ss) = match fields[..] {
+        [mm, ss] => ("0", mm, ss),
+        [hh, mm, ss] => (hh, mm, ss),
+        _ => return None,
+    };
+
+    let hh: f64 = hh.trim().parse().ok()?;
+    let mm: f64 = mm.trim().parse().ok()?;
+    let ss: f64 = ss.trim().parse().ok()?;
+
+    Some(hh * 3600.0 + mm * 60.0 + ss)
+}
+
+fn strip_tags(str: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in str.chars() {
+        match c {
+            '<' | '{' => in_tag = true,
+            '>' | '}' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => (),
+        }
+    }
+
+    text.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[TranscriptLine]) -> Vec<(f64, &str)> {
+        lines
+            .iter()
+            .map(|l| ((l.time * 1000.0).round() / 1000.0, l.text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn lrc_multiple_timestamps() {
+        let lrc =
+            "[ar:Author]\n[ti:Book]\n[00:12.00][01:15.30] Refrain\n[00:20.5]Verse\n[00:30.00]\n";
+        assert_eq!(
+            lines(&parse_lrc(lrc)),
+            [(12.0, "Refrain"), (75.3, "Refrain"), (20.5, "Verse")]
+        );
+    }
+
+    #[test]
+    fn lrc_offset() {
+        let lrc = "[offset:+500]\n[00:00.20]First\n[01:00.00]Second\n";
+        assert_eq!(lines(&parse_lrc(lrc)), [(0.0, "First"), (59.5, "Second")]);
+
+        let lrc = "[offset:-1000]\n[00:01.00]First\n";
+        assert_eq!(lines(&parse_lrc(lrc)), [(2.0, "First")]);
+    }
+
+    #[test]
+    fn srt_cues() {
+        let srt = "1\r\n00:00:01,500 --> 00:00:04,000\r\nHello <i>there</i>\r\nsecond line\r\n\r\n\
+                   2\r\n01:02:03,045 --> 01:02:05,000\r\n{\\an8}Top\r\n";
+        assert_eq!(
+            lines(&parse_cues(srt)),
+            [(1.5, "Hello there second line"), (3723.045, "Top")]
+        );
+    }
+
+    #[test]
+    fn vtt_cues() {
+        let vtt = "WEBVTT - Book\n\nNOTE written by hand\n\n\
+                   00:01.000 --> 00:04.000 align:start position:10%\nFirst\n\n\
+                   intro-2\n01:00:02.250 --> 01:00:03.000\n<v Narrator>Second\n";
+        assert_eq!(
+            lines(&parse_cues(vtt)),
+            [(1.0, "First"), (3602.25, "Second")]
+        );
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(parse_timestamp("01:15.30"), Some(75.3));
+        assert_eq!(parse_timestamp("01:00:00,500"), Some(3600.5));
+        assert_eq!(parse_timestamp("ar:Author"), None);
+        assert_eq!(parse_timestamp("12.5"), None);
+    }
+
+    #[test]
+    fn current_line() {
+        let lines = parse_lrc("[00:10.00]One\n[00:20.00]Two\n");
+        assert_eq!(line_at(&lines, 5.0), None);
+        assert_eq!(line_at(&lines, 10.0), Some(0));
+        assert_eq!(line_at(&lines, 25.0), Some(1));
+    }
+}
@@ -2,7 +2,16 @@ mod commands;
 mod keybindings;
 
 use crate::UAPlayerError;
-use crate::libmpv_handler::{LibMpvEventMessage, LibMpvMessage};
+use crate::bookmarks::Bookmark;
+use crate::config::{Config, NormalizationMode};
+use crate::eq::{FLAT_PRESET, preset_names};
+use crate::history::HistoryEntry;
+use crate::libmpv_handler::{AudioDevice, LibMpvEventMessage, LibMpvMessage, LoopData};
+use crate::library::LibraryEntry;
+use crate::queue::queue_entry_name;
+use crate::recent::RecentEntry;
+use crate::timer::{TimerAction, TimerStatus};
+use crate::transcript::{TranscriptLine, line_at};
 use crate::tui::commands::{
     TuiCommand, TuiState, generate_completion_suggestions, map_str_to_tuicommand,
 };
@@ -23,12 +32,13 @@ struct Chapter {
 pub fn tui(
     libmpv_s: crossbeam::channel::Sender<LibMpvMessage>,
     tui_r: crossbeam::channel::Receiver<LibMpvEventMessage>,
+    config: &Config,
 ) -> Result<(), UAPlayerError> {
     let mut command_mode = false;
     let mut command_text = "".to_string();
     let mut command_error = "".to_string();
     let mut cursor_position: u16 = 0;
-    let mut command_suggestions: Option<Vec<&str>> = None;
+    let mut command_suggestions: Option<Vec<String>> = None;
     let mut command_suggestions_index: Option<usize> = None;
 
     let keybindings = keybindings::Keybindings::new();
@@ -43,52 +53,78 @@ pub fn tui(
     let mut scroll: u16 = 0;
     let mut scroll_to_center: bool = false;
 
+    let mut library: Vec<LibraryEntry> = vec![];
+    let mut library_r: Option<crossbeam::channel::Receiver<LibraryEntry>> = None;
+    let mut recent: Vec<RecentEntry> = vec![];
+    let mut queue: Vec<String> = vec![];
+    let mut audio_devices: Vec<AudioDevice> = vec![];
+    let mut audio_device = String::new();
+    let mut history: Vec<HistoryEntry> = vec![];
+    let mut bookmarks: Vec<Bookmark> = vec![];
+    let mut transcript: Vec<TranscriptLine> = vec![];
+    let mut transcript_line: Option<usize> = None;
+    let mut selected: usize = 0;
+
     let mut playback_start = std::time::SystemTime::now();
     let mut playback_start_offset = 0.0;
     let mut playback_paused = true;
     let mut playback_ready = false;
     let mut playback_duration = 0;
     let mut playback_volume = 0;
+    let mut playback_speed = 1.0;
+    let mut skip_silence = false;
+    let mut time_saved = 0.0;
+    let mut normalization = NormalizationMode::Off;
+    let mut compressor = false;
+    let mut eq = FLAT_PRESET.to_string();
+    let mut mono = false;
+    let mut balance = 0.0;
+    let eq_presets = preset_names(&config.eq);
+    let mut ab_loop = LoopData::default();
 
-    let mut pause_after = None;
-    let mut pause_after_timer: Option<std::time::SystemTime> = None;
-    let mut pause_after_duration: Option<std::time::Duration> = None;
-    let mut quit_after = None;
-    let mut quit_after_timer: Option<std::time::SystemTime> = None;
-    let mut quit_after_duration: Option<std::time::Duration> = None;
+    let mut timers: Vec<TimerStatus> = vec![];
+    let mut fading = false;
 
     loop {
-        let mut timer_text = None;
-        if let Some(pause_after_timer) = pause_after_timer {
-            let elapsed = pause_after_timer.elapsed();
-            let pause_after_duration = pause_after_duration.unwrap();
-            if let Ok(elapsed) = elapsed {
-                let pause_time_left: std::time::Duration =
-                    pause_after_duration.saturating_sub(elapsed);
-                timer_text = Some(format!("P: {}", secs_to_hms(pause_time_left.as_secs())));
+        let playback_time = {
+            if !playback_ready {
+                0.0
+            } else if playback_paused {
+                playback_start_offset
+            } else {
+                playback_start_offset + playback_start.elapsed()?.as_secs_f64() * playback_speed
             }
-        }
-        if let Some(quit_after_timer) = quit_after_timer {
-            let elapsed = quit_after_timer.elapsed();
-            let quit_after_duration = quit_after_duration.unwrap();
-            if let Ok(elapsed) = elapsed {
-                let quit_time_left: std::time::Duration =
-                    quit_after_duration.saturating_sub(elapsed);
-                timer_text = Some(format!("Q: {}", secs_to_hms(quit_time_left.as_secs())));
+        };
+
+        let current_line = line_at(&transcript, playback_time);
+        if current_line != transcript_line {
+            transcript_line = current_line;
+            if tui_state == TuiState::Transcript {
+                scroll_to_center = true;
             }
         }
 
+        let mut timer_text = timers
+            .iter()
+            .min_by(|a, b| a.left.total_cmp(&b.left))
+            .map(|t| {
+                let action = match t.action {
+                    TimerAction::Pause => "P",
+                    TimerAction::Quit => "Q",
+                };
+                let mut text = format!("{action}: {}", secs_to_hms(t.left.ceil() as u64));
+                if timers.len() > 1 {
+                    text.push_str(&format!(" +{}", timers.len() - 1));
+                }
+                text
+            });
+        let still_listening_prompt = fading && config.sleep_timer.still_listening;
+        if still_listening_prompt {
+            timer_text = timer_text.map(|t| format!("Still listening? Press any key {t}"));
+        }
+
         match tui_state {
             TuiState::Player => {
-                let playback_time = {
-                    if !playback_ready {
-                        0.0
-                    } else if playback_paused {
-                        playback_start_offset
-                    } else {
-                        playback_start_offset + playback_start.elapsed()?.as_secs_f64()
-                    }
-                };
                 let mut playback_time = playback_time.floor() as u64;
                 playback_time = playback_time.min(playback_duration);
                 let symbol = {
@@ -106,13 +142,63 @@ pub fn tui(
                 if let Some(chapter) = chapter.as_ref() {
                     to_draw.push_str(&format!("\n{chapter}",));
                 }
+                let time_left = (playback_duration - playback_time) as f64 / playback_speed;
                 to_draw.push_str(&format!(
-                    "\n{} {} / {} vol: {}",
+                    "\n{} {} / {} (-{}) vol: {} speed: {:.2}x",
                     symbol,
                     secs_to_hms(playback_time),
                     secs_to_hms(playback_duration),
-                    playback_volume
+                    secs_to_hms(time_left.floor() as u64),
+                    playback_volume,
+                    playback_speed
                 ));
+                if skip_silence {
+                    to_draw.push_str(&format!(
+                        " skip-silence (saved {})",
+                        secs_to_hms(time_saved as u64)
+                    ));
+                }
+                match normalization {
+                    NormalizationMode::Off => (),
+                    NormalizationMode::Loudnorm => to_draw.push_str(" norm: loudnorm"),
+                    NormalizationMode::Dynaudnorm => to_draw.push_str(" norm: dynaudnorm"),
+                }
+                if compressor {
+                    to_draw.push_str(" compressor");
+                }
+                if eq != FLAT_PRESET {
+                    to_draw.push_str(&format!(" eq: {eq}"));
+                }
+                if mono {
+                    to_draw.push_str(" mono");
+                }
+                if balance != 0.0 {
+                    to_draw.push_str(&format!(" balance: {balance:+.2}"));
+                }
+                if ab_loop.repeat_chapter {
+                    to_draw.push_str(" repeat-chapter");
+                } else if ab_loop.a.is_some() || ab_loop.b.is_some() {
+                    let point =
+                        |p: Option<f64>| p.map_or("-".to_string(), |p| secs_to_hms(p as u64));
+                    to_draw.push_str(&format!(
+                        " loop: {} - {}",
+                        point(ab_loop.a),
+                        point(ab_loop.b)
+                    ));
+                }
+                if let Some(i) = transcript_line {
+                    to_draw.push('\n');
+                    let end = (i + 2).min(transcript.len());
+                    for (j, line) in transcript
+                        .iter()
+                        .enumerate()
+                        .take(end)
+                        .skip(i.saturating_sub(1))
+                    {
+                        let marker = if j == i { ">" } else { " " };
+                        to_draw.push_str(&format!("\n{marker} {}", line.text));
+                    }
+                }
                 draw(
                     &mut terminal,
                     &to_draw,
@@ -164,6 +250,303 @@ pub fn tui(
                 )?;
                 scroll_to_center = false;
             }
+            TuiState::Library => {
+                let mut to_draw = "".to_string();
+                if config.library.is_empty() {
+                    to_draw.push_str("No library directories set in config.json\n");
+                } else if library.is_empty() {
+                    to_draw.push_str("Scanning library...\n");
+                }
+                library.iter().enumerate().for_each(|(i, x)| {
+                    if i == selected {
+                        to_draw.push_str("* ")
+                    };
+                    to_draw.push_str(&x.title);
+                    if let Some(ref author) = x.author {
+                        to_draw.push_str(&format!(" by {author}"));
+                    }
+                    to_draw.push_str(&format!(
+                        " || {} || {}%\n",
+                        secs_to_hms(x.duration.floor() as u64),
+                        x.percent_listened()
+                    ));
+                });
+
+                draw(
+                    &mut terminal,
+                    &to_draw,
+                    if command_mode {
+                        Some(&command_text)
+                    } else {
+                        None
+                    },
+                    cursor_position,
+                    if command_error.trim().is_empty() {
+                        None
+                    } else {
+                        Some(&command_error)
+                    },
+                    timer_text.as_deref(),
+                    &mut scroll,
+                    scroll_to_center,
+                )?;
+                scroll_to_center = false;
+            }
+            TuiState::Recent => {
+                let mut to_draw = "".to_string();
+                recent.iter().enumerate().for_each(|(i, x)| {
+                    if i == selected {
+                        to_draw.push_str("* ")
+                    };
+                    to_draw.push_str(&format!(
+                        "{} || {} || {}\n",
+                        x.name(),
+                        secs_to_hms(x.position.floor() as u64),
+                        x.last_played_str()
+                    ));
+                });
+
+                draw(
+                    &mut terminal,
+                    &to_draw,
+                    if command_mode {
+                        Some(&command_text)
+                    } else {
+                        None
+                    },
+                    cursor_position,
+                    if command_error.trim().is_empty() {
+                        None
+                    } else {
+                        Some(&command_error)
+                    },
+                    timer_text.as_deref(),
+                    &mut scroll,
+                    scroll_to_center,
+                )?;
+                scroll_to_center = false;
+            }
+            TuiState::Queue => {
+                let mut to_draw = "".to_string();
+                if queue.is_empty() {
+                    to_draw.push_str("Queue is empty\n");
+                }
+                queue.iter().enumerate().for_each(|(i, x)| {
+                    if i == selected {
+                        to_draw.push_str("* ")
+                    };
+                    to_draw.push_str(&format!("{}. {}\n", i + 1, queue_entry_name(x)));
+                });
+
+                draw(
+                    &mut terminal,
+                    &to_draw,
+                    if command_mode {
+                        Some(&command_text)
+                    } else {
+                        None
+                    },
+                    cursor_position,
+                    if command_error.trim().is_empty() {
+                        None
+                    } else {
+                        Some(&command_error)
+                    },
+                    timer_text.as_deref(),
+                    &mut scroll,
+                    scroll_to_center,
+                )?;
+                scroll_to_center = false;
+            }
+            TuiState::Devices => {
+                let mut to_draw = "".to_string();
+                if audio_devices.is_empty() {
+                    to_draw.push_str("No audio devices\n");
+                }
+                audio_devices.iter().enumerate().for_each(|(i, x)| {
+                    if i == selected {
+                        to_draw.push_str("* ")
+                    };
+                    to_draw.push_str(&format!("{} || {}", x.description, x.name));
+                    if x.name == audio_device {
+                        to_draw.push_str(" (current)");
+                    }
+                    to_draw.push('\n');
+                });
+
+                draw(
+                    &mut terminal,
+                    &to_draw,
+                    if command_mode {
+                        Some(&command_text)
+                    } else {
+                        None
+                    },
+                    cursor_position,
+                    if command_error.trim().is_empty() {
+                        None
+                    } else {
+                        Some(&command_error)
+                    },
+                    timer_text.as_deref(),
+                    &mut scroll,
+                    scroll_to_center,
+                )?;
+                scroll_to_center = false;
+            }
+            TuiState::Bookmarks => {
+                let mut to_draw = "".to_string();
+                if bookmarks.is_empty() {
+                    to_draw.push_str("No bookmarks\n");
+                }
+                bookmarks.iter().enumerate().for_each(|(i, x)| {
+                    if i == selected {
+                        to_draw.push_str("* ")
+                    };
+                    to_draw.push_str(&format!(
+                        "{}. {} || {} || {}\n",
+                        i + 1,
+                        secs_to_hms(x.time.floor() as u64),
+                        x.chapter.as_deref().unwrap_or("-"),
+                        x.note
+                    ));
+                });
+
+                draw(
+                    &mut terminal,
+                    &to_draw,
+                    if command_mode {
+                        Some(&command_text)
+                    } else {
+                        None
+                    },
+                    cursor_position,
+                    if command_error.trim().is_empty() {
+                        None
+                    } else {
+                        Some(&command_error)
+                    },
+                    timer_text.as_deref(),
+                    &mut scroll,
+                    scroll_to_center,
+                )?;
+                scroll_to_center = false;
+            }
+            TuiState::History => {
+                let mut to_draw = "".to_string();
+                if history.is_empty() {
+                    to_draw.push_str("No jumps yet\n");
+                }
+                history.iter().enumerate().for_each(|(i, x)| {
+                    if i == selected {
+                        to_draw.push_str("* ")
+                    };
+                    to_draw.push_str(&format!(
+                        "{} || {} || {}\n",
+                        secs_to_hms(x.time.floor() as u64),
+                        x.chapter.as_deref().unwrap_or("-"),
+                        x.at_str()
+                    ));
+                });
+
+                draw(
+                    &mut terminal,
+                    &to_draw,
+                    if command_mode {
+                        Some(&command_text)
+                    } else {
+                        None
+                    },
+                    cursor_position,
+                    if command_error.trim().is_empty() {
+                        None
+                    } else {
+                        Some(&command_error)
+                    },
+                    timer_text.as_deref(),
+                    &mut scroll,
+                    scroll_to_center,
+                )?;
+                scroll_to_center = false;
+            }
+            TuiState::Transcript => {
+                let mut to_draw = "".to_string();
+                if transcript.is_empty() {
+                    to_draw.push_str("No transcript found next to the book\n");
+                }
+                transcript.iter().enumerate().for_each(|(i, x)| {
+                    if Some(i) == transcript_line {
+                        to_draw.push_str("* ")
+                    };
+                    to_draw.push_str(&format!(
+                        "{} || {}\n",
+                        secs_to_hms(x.time.floor() as u64),
+                        x.text
+                    ));
+                });
+
+                draw(
+                    &mut terminal,
+                    &to_draw,
+                    if command_mode {
+                        Some(&command_text)
+                    } else {
+                        None
+                    },
+                    cursor_position,
+                    if command_error.trim().is_empty() {
+                        None
+                    } else {
+                        Some(&command_error)
+                    },
+                    timer_text.as_deref(),
+                    &mut scroll,
+                    scroll_to_center,
+                )?;
+                scroll_to_center = false;
+            }
+            TuiState::Timers => {
+                let mut to_draw = "".to_string();
+                if timers.is_empty() {
+                    to_draw.push_str("No timers\n");
+                }
+                timers.iter().enumerate().for_each(|(i, x)| {
+                    if i == selected {
+                        to_draw.push_str("* ")
+                    };
+                    let action = match x.action {
+                        TimerAction::Pause => "pause",
+                        TimerAction::Quit => "quit",
+                    };
+                    to_draw.push_str(&format!(
+                        "{} || {} || {} || {}\n",
+                        x.name,
+                        action,
+                        x.kind,
+                        secs_to_hms(x.left.ceil() as u64)
+                    ));
+                });
+
+                draw(
+                    &mut terminal,
+                    &to_draw,
+                    if command_mode {
+                        Some(&command_text)
+                    } else {
+                        None
+                    },
+                    cursor_position,
+                    if command_error.trim().is_empty() {
+                        None
+                    } else {
+                        Some(&command_error)
+                    },
+                    timer_text.as_deref(),
+                    &mut scroll,
+                    scroll_to_center,
+                )?;
+                scroll_to_center = false;
+            }
             TuiState::Help => {
                 let min_width = 12;
                 let mut to_draw = generate_help_str(min_width);
@@ -197,6 +580,10 @@ pub fn tui(
                 let mut command = None;
                 if let event::Event::Key(key) = event {
                     command_error = "".to_string();
+                    // Any key answers the prompt, bound keys still run their command.
+                    if still_listening_prompt {
+                        libmpv_s.send(LibMpvMessage::ExtendTimers)?;
+                    }
                     if command_mode {
                         if key.code != event::KeyCode::Tab && key.code != event::KeyCode::BackTab {
                             command_suggestions_index = None;
@@ -205,7 +592,7 @@ pub fn tui(
 
                         if key.code.to_string().len() == 1 {
                             let c = key.code.to_string().chars().next().unwrap();
-                            if c.is_alphanumeric() || c == '-' || c == '+' || c == ':' {
+                            if c.is_alphanumeric() || c.is_ascii_graphic() {
                                 if cursor_position == command_text.len() as u16 {
                                     command_text.push(c);
                                 } else {
@@ -249,7 +636,8 @@ pub fn tui(
                             || key.code == event::KeyCode::BackTab
                         {
                             if command_suggestions.is_none() {
-                                let suggestions = generate_completion_suggestions(&command_text);
+                                let suggestions =
+                                    generate_completion_suggestions(&command_text, &eq_presets);
                                 if !suggestions.is_empty() {
                                     command_suggestions = Some(suggestions);
                                 }
@@ -266,8 +654,7 @@ pub fn tui(
                                 };
 
                                 command_suggestions_index = Some(i);
-                                let suggestion = suggestions.get(i).unwrap().to_owned();
-                                command_text = suggestion.to_string();
+                                command_text = suggestions.get(i).unwrap().to_owned();
                                 cursor_position = command_text.len() as u16;
                             }
                         }
@@ -277,6 +664,23 @@ pub fn tui(
                     if let Some(command) = command {
                         match command {
                             TuiCommand::State(state) => {
+                                if state == TuiState::Library && library_r.is_none() {
+                                    let (library_s, r) = crossbeam::channel::unbounded();
+                                    let roots = config.library.clone();
+                                    std::thread::spawn(move || {
+                                        crate::library::load(&roots, library_s)
+                                    });
+                                    library_r = Some(r);
+                                } else if state == TuiState::Library {
+                                    crate::library::refresh_progress(&mut library);
+                                }
+                                if state == TuiState::Recent {
+                                    recent = crate::recent::load_recent();
+                                }
+                                if state == TuiState::Devices {
+                                    libmpv_s.send(LibMpvMessage::ListAudioDevices)?;
+                                }
+                                selected = 0;
                                 tui_state = state.clone();
                                 scroll_to_center = true;
                             }
@@ -290,12 +694,64 @@ pub fn tui(
                             TuiCommand::SetVolume(vol) => {
                                 libmpv_s.send(LibMpvMessage::SetVolume(vol))?;
                             }
+                            TuiCommand::Speed(offset) => {
+                                libmpv_s.send(LibMpvMessage::UpdateSpeed(offset))?;
+                            }
+                            TuiCommand::SetSpeed(speed) => {
+                                libmpv_s.send(LibMpvMessage::SetSpeed(speed))?;
+                            }
                             TuiCommand::Seek(offset) => {
                                 libmpv_s.send(LibMpvMessage::UpdatePosition(offset))?;
                             }
                             TuiCommand::SetPosition(pos) => {
                                 libmpv_s.send(LibMpvMessage::SetPosition(pos))?;
                             }
+                            TuiCommand::UndoSeek => {
+                                libmpv_s.send(LibMpvMessage::UndoSeek)?;
+                            }
+                            TuiCommand::RedoSeek => {
+                                libmpv_s.send(LibMpvMessage::RedoSeek)?;
+                            }
+                            TuiCommand::BookmarkAdd(note) => {
+                                libmpv_s.send(LibMpvMessage::AddBookmark(note))?;
+                            }
+                            TuiCommand::BookmarkDelete(i) => {
+                                let i = match i {
+                                    Some(i) => Some(i),
+                                    None if tui_state == TuiState::Bookmarks => {
+                                        Some(selected).filter(|&i| i < bookmarks.len())
+                                    }
+                                    None => None,
+                                };
+                                match i {
+                                    Some(i) => libmpv_s.send(LibMpvMessage::DeleteBookmark(i))?,
+                                    None => {
+                                        command_error = "Error: no bookmark selected".to_string()
+                                    }
+                                }
+                            }
+                            TuiCommand::BookmarkGoto(i) => match bookmarks.get(i) {
+                                Some(bookmark) => {
+                                    libmpv_s.send(LibMpvMessage::SetPosition(bookmark.time))?
+                                }
+                                None => command_error = format!("Error: no bookmark {}", i + 1),
+                            },
+                            TuiCommand::ClipExport(clip) => {
+                                libmpv_s.send(LibMpvMessage::ExportClip(clip))?;
+                            }
+                            TuiCommand::BookmarkExport((from, to, path)) => {
+                                match (bookmarks.get(from), bookmarks.get(to)) {
+                                    (Some(from), Some(to)) => libmpv_s.send(
+                                        LibMpvMessage::ExportClip((from.time, to.time, path)),
+                                    )?,
+                                    (None, _) => {
+                                        command_error = format!("Error: no bookmark {}", from + 1)
+                                    }
+                                    (_, None) => {
+                                        command_error = format!("Error: no bookmark {}", to + 1)
+                                    }
+                                }
+                            }
                             TuiCommand::PlayPause => {
                                 libmpv_s.send(LibMpvMessage::PlayPause)?;
                             }
@@ -305,34 +761,147 @@ pub fn tui(
                             TuiCommand::NextChapter => {
                                 libmpv_s.send(LibMpvMessage::NextChapter)?;
                             }
-                            TuiCommand::PauseAfter(min) => {
-                                pause_after = Some(crossbeam::channel::after(
-                                    std::time::Duration::from_mins(min),
-                                ));
-                                pause_after_duration = Some(std::time::Duration::from_mins(min));
-                                pause_after_timer = Some(std::time::SystemTime::now());
-                                quit_after = None;
-                                quit_after_duration = None;
-                                quit_after_timer = None;
-                            }
-                            TuiCommand::QuitAfter(min) => {
-                                quit_after = Some(crossbeam::channel::after(
-                                    std::time::Duration::from_mins(min),
-                                ));
-                                quit_after_duration = Some(std::time::Duration::from_mins(min));
-                                quit_after_timer = Some(std::time::SystemTime::now());
-                                pause_after = None;
-                                pause_after_duration = None;
-                                pause_after_timer = None;
+                            TuiCommand::TimerAdd(request) => {
+                                libmpv_s.send(LibMpvMessage::AddTimer(request))?;
+                            }
+                            TuiCommand::TimerCancel(name) => {
+                                libmpv_s.send(LibMpvMessage::CancelTimer(name))?;
                             }
                             TuiCommand::EnterCommandMode(enter) => {
                                 command_mode = enter;
                             }
-                            TuiCommand::Scroll(x) => {
-                                if x > 0 && scroll < (chapters.len() - 1) as u16 {
-                                    scroll += 1;
-                                } else if x < 0 && scroll > 0 {
-                                    scroll -= 1;
+                            TuiCommand::Scroll(x) => match tui_state {
+                                TuiState::Library
+                                | TuiState::Recent
+                                | TuiState::Queue
+                                | TuiState::Timers
+                                | TuiState::Devices
+                                | TuiState::History
+                                | TuiState::Bookmarks => {
+                                    let len = match tui_state {
+                                        TuiState::Library => library.len(),
+                                        TuiState::Recent => recent.len(),
+                                        TuiState::Queue => queue.len(),
+                                        TuiState::Timers => timers.len(),
+                                        TuiState::Devices => audio_devices.len(),
+                                        TuiState::History => history.len(),
+                                        _ => bookmarks.len(),
+                                    };
+                                    if x > 0 && selected + 1 < len {
+                                        selected += 1;
+                                    } else if x < 0 && selected > 0 {
+                                        selected -= 1;
+                                    }
+                                    scroll_to_center = true;
+                                }
+                                _ => {
+                                    let len = match tui_state {
+                                        TuiState::Transcript => transcript.len(),
+                                        _ => chapters.len(),
+                                    };
+                                    if x > 0 && scroll < len.saturating_sub(1) as u16 {
+                                        scroll += 1;
+                                    } else if x < 0 && scroll > 0 {
+                                        scroll -= 1;
+                                    }
+                                }
+                            },
+                            TuiCommand::Select => match tui_state {
+                                TuiState::Library => {
+                                    if let Some(entry) = library.get(selected) {
+                                        libmpv_s
+                                            .send(LibMpvMessage::LoadBook(entry.path.clone()))?;
+                                        tui_state = TuiState::Player;
+                                    }
+                                }
+                                TuiState::Recent => {
+                                    if let Some(entry) = recent.get(selected) {
+                                        libmpv_s
+                                            .send(LibMpvMessage::LoadBook(entry.path.clone()))?;
+                                        tui_state = TuiState::Player;
+                                    }
+                                }
+                                TuiState::Queue => {
+                                    if let Some(path) = queue.get(selected) {
+                                        libmpv_s.send(LibMpvMessage::LoadBook(path.clone()))?;
+                                        tui_state = TuiState::Player;
+                                    }
+                                }
+                                TuiState::Bookmarks => {
+                                    if let Some(bookmark) = bookmarks.get(selected) {
+                                        libmpv_s.send(LibMpvMessage::SetPosition(bookmark.time))?;
+                                        tui_state = TuiState::Player;
+                                    }
+                                }
+                                TuiState::History => {
+                                    if let Some(entry) = history.get(selected) {
+                                        libmpv_s.send(LibMpvMessage::SetPosition(entry.time))?;
+                                        tui_state = TuiState::Player;
+                                    }
+                                }
+                                TuiState::Devices => {
+                                    if let Some(device) = audio_devices.get(selected) {
+                                        libmpv_s.send(LibMpvMessage::SetAudioDevice(
+                                            device.name.clone(),
+                                        ))?;
+                                        tui_state = TuiState::Player;
+                                    }
+                                }
+                                _ => (),
+                            },
+                            TuiCommand::QueueAdd(path) => {
+                                libmpv_s.send(LibMpvMessage::QueueAdd(path))?;
+                            }
+                            TuiCommand::SkipSilence(enabled) => {
+                                libmpv_s.send(LibMpvMessage::SkipSilence(enabled))?;
+                            }
+                            TuiCommand::Normalization(mode) => {
+                                libmpv_s.send(LibMpvMessage::Normalization(mode))?;
+                            }
+                            TuiCommand::Compressor(enabled) => {
+                                libmpv_s.send(LibMpvMessage::Compressor(enabled))?;
+                            }
+                            TuiCommand::Eq(preset) => {
+                                libmpv_s.send(LibMpvMessage::Eq(preset))?;
+                            }
+                            TuiCommand::Mono(enabled) => {
+                                libmpv_s.send(LibMpvMessage::Mono(enabled))?;
+                            }
+                            TuiCommand::Balance(value) => {
+                                libmpv_s.send(LibMpvMessage::Balance(value))?;
+                            }
+                            TuiCommand::QueueClear => {
+                                libmpv_s.send(LibMpvMessage::QueueClear)?;
+                            }
+                            TuiCommand::LoopA => {
+                                libmpv_s.send(LibMpvMessage::LoopA)?;
+                            }
+                            TuiCommand::LoopB => {
+                                libmpv_s.send(LibMpvMessage::LoopB)?;
+                            }
+                            TuiCommand::LoopClear => {
+                                libmpv_s.send(LibMpvMessage::LoopClear)?;
+                            }
+                            TuiCommand::RepeatChapter(enabled) => {
+                                libmpv_s.send(LibMpvMessage::RepeatChapter(enabled))?;
+                            }
+                            TuiCommand::TranscriptSearch(text) => {
+                                // Searches forward from the current line, wrapping around.
+                                let query = text.to_lowercase();
+                                let start = transcript_line.map_or(0, |i| i + 1);
+                                let found = (0..transcript.len())
+                                    .map(|i| &transcript[(start + i) % transcript.len()])
+                                    .find(|l| l.text.to_lowercase().contains(&query));
+                                match found {
+                                    Some(line) => {
+                                        libmpv_s.send(LibMpvMessage::SetPosition(line.time))?
+                                    }
+                                    None if transcript.is_empty() => {
+                                        command_error = "Error: no transcript".to_string()
+                                    }
+                                    None => {
+                                        command_error = format!("Error: {text} not in transcript")
+                                    }
                                 }
                             }
                         }
@@ -355,27 +924,16 @@ pub fn tui(
                     playback_start = std::time::SystemTime::now();
                     playback_duration = data.duration.floor() as u64;
                     playback_volume = data.volume;
+                    playback_speed = data.speed;
                     title = data.media_title;
                     chapter = data.chapter.0;
                     chapter_num = data.chapter.1;
-                    chapters = data
-                        .chapters
-                        .iter()
-                        .map(|c| Chapter {
-                            title: c.title.clone(),
-                            start_time: secs_to_hms(c.time as u64),
-                            end_time: "".to_string(),
-                        })
-                        .collect();
+                    chapters = build_chapters(&data.chapters, playback_duration);
                     artist = data.artist;
-                    for i in 0..chapters.len() - 1 {
-                        chapters[i].end_time = chapters[i + 1].start_time.clone();
-                    }
-                    let last = chapters.len() - 1;
-                    chapters[last].end_time = secs_to_hms(playback_duration);
                 }
                 LibMpvEventMessage::PlaybackPause => {
-                    playback_start_offset += playback_start.elapsed()?.as_secs_f64();
+                    playback_start_offset +=
+                        playback_start.elapsed()?.as_secs_f64() * playback_speed;
                     playback_paused = true;
                 }
                 LibMpvEventMessage::PlaybackResume => {
@@ -385,6 +943,43 @@ pub fn tui(
                 LibMpvEventMessage::VolumeUpdate(vol) => {
                     playback_volume = vol;
                 }
+                LibMpvEventMessage::TimersUpdate((new_timers, new_fading)) => {
+                    timers = new_timers;
+                    fading = new_fading;
+                    if tui_state == TuiState::Timers {
+                        selected = selected.min(timers.len().saturating_sub(1));
+                    }
+                }
+                LibMpvEventMessage::SpeedUpdate(speed) => {
+                    playback_speed = speed;
+                }
+                LibMpvEventMessage::SkipSilenceUpdate(enabled) => {
+                    skip_silence = enabled;
+                }
+                LibMpvEventMessage::NormalizationUpdate((mode, enabled)) => {
+                    normalization = mode;
+                    compressor = enabled;
+                }
+                LibMpvEventMessage::AudioDevicesUpdate((devices, current)) => {
+                    audio_devices = devices;
+                    audio_device = current;
+                    if tui_state == TuiState::Devices {
+                        selected = selected.min(audio_devices.len().saturating_sub(1));
+                    }
+                }
+                LibMpvEventMessage::ChannelsUpdate((new_mono, new_balance)) => {
+                    mono = new_mono;
+                    balance = new_balance;
+                }
+                LibMpvEventMessage::EqUpdate(preset) => {
+                    eq = preset;
+                }
+                LibMpvEventMessage::TimeSavedUpdate(saved) => {
+                    time_saved = saved;
+                }
+                LibMpvEventMessage::LoopUpdate(data) => {
+                    ab_loop = data;
+                }
                 LibMpvEventMessage::PositionUpdate(pos) => {
                     playback_start = std::time::SystemTime::now();
                     playback_start_offset = pos;
@@ -393,24 +988,45 @@ pub fn tui(
                     chapter = Some(chap.0);
                     chapter_num = chap.1;
                 }
+                LibMpvEventMessage::BookmarksUpdate(new_bookmarks) => {
+                    bookmarks = new_bookmarks;
+                    if tui_state == TuiState::Bookmarks {
+                        selected = selected.min(bookmarks.len().saturating_sub(1));
+                    }
+                }
+                LibMpvEventMessage::TranscriptUpdate(lines) => {
+                    transcript = lines;
+                    transcript_line = None;
+                }
+                LibMpvEventMessage::HistoryUpdate(entries) => {
+                    history = entries;
+                    if tui_state == TuiState::History {
+                        selected = selected.min(history.len().saturating_sub(1));
+                    }
+                }
+                LibMpvEventMessage::ChaptersUpdate(new_chapters) => {
+                    chapters = build_chapters(&new_chapters, playback_duration);
+                    scroll = 0;
+                }
+                LibMpvEventMessage::QueueUpdate(new_queue) => {
+                    queue = new_queue;
+                    if tui_state == TuiState::Queue {
+                        selected = selected.min(queue.len().saturating_sub(1));
+                    }
+                }
+                LibMpvEventMessage::Status(status) => {
+                    command_error = status;
+                }
+                LibMpvEventMessage::Error(err) => {
+                    command_error = err;
+                }
                 LibMpvEventMessage::Quit => break,
             }
         }
-
-        if pause_after
-            .as_ref()
-            .and_then(|x| x.try_recv().ok())
-            .is_some()
-        {
-            libmpv_s.send(LibMpvMessage::Pause)?;
-        }
-        if quit_after
-            .as_ref()
-            .and_then(|x| x.try_recv().ok())
-            .is_some()
-        {
-            libmpv_s.send(LibMpvMessage::Quit)?;
-            break;
+        if let Some(ref library_r) = library_r {
+            while let Ok(entry) = library_r.try_recv() {
+                library.push(entry);
+            }
         }
     }
 
@@ -481,6 +1097,25 @@ pub fn draw(
     Ok(())
 }
 
+fn build_chapters(chapters: &[crate::libmpv_handler::Chapter], duration: u64) -> Vec<Chapter> {
+    let mut chapters: Vec<Chapter> = chapters
+        .iter()
+        .map(|c| Chapter {
+            title: c.title.clone(),
+            start_time: secs_to_hms(c.time as u64),
+            end_time: "".to_string(),
+        })
+        .collect();
+    for i in 1..chapters.len() {
+        chapters[i - 1].end_time = chapters[i].start_time.clone();
+    }
+    if let Some(last) = chapters.last_mut() {
+        last.end_time = secs_to_hms(duration);
+    }
+
+    chapters
+}
+
 fn secs_to_hms(seconds: u64) -> String {
     let h = seconds / 3600;
     let m = (seconds - h * 3600) / 60;
@@ -500,12 +1135,79 @@ pub fn generate_help_str(min_width: usize) -> String {
         "global", "vol=[+|-]<i64>"
     )
     .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "speed=[+|-]<f64>"
+    )
+    .unwrap();
     writeln!(
         help_str,
         "{:min_width$} {:min_width$}",
         "global", "seek=[+|-]<f64>"
     )
     .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "undo-seek"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "redo-seek"
+    )
+    .unwrap();
+    writeln!(help_str, "{:min_width$} {:min_width$}", "global", "history").unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "bookmark [note]"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "bookmarks"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "bookmark-delete [n]"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "bookmark-goto <n>"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "bookmark-export <n> <n> [path]"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "clip-export <start> <end> [path]"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "transcript"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "transcript-search <text>"
+    )
+    .unwrap();
     writeln!(
         help_str,
         "{:min_width$} {:min_width$}",
@@ -528,19 +1230,89 @@ pub fn generate_help_str(min_width: usize) -> String {
     writeln!(
         help_str,
         "{:min_width$} {:min_width$}",
-        "global", "pause-after=<u64>"
+        "global", "pause-after=<u64|chapter[+N]>"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "quit-after=<u64|chapter[+N]>"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "timer add <name> <pause|quit> <u64|chapter[+N]> [wall|listened]"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "timer <list|cancel [name]>"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global",
+        "view <player|chapters|library|recent|queue|timers|devices|history|bookmarks|transcript|help>"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "queue <add <path>|list|clear>"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "skip-silence <on|off>"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "normalize <off|loudnorm|dynaudnorm>"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "compressor <on|off>"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "eq <preset>"
+    )
+    .unwrap();
+    writeln!(help_str, "{:min_width$} {:min_width$}", "global", "devices").unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "mono <on|off>"
+    )
+    .unwrap();
+    writeln!(
+        help_str,
+        "{:min_width$} {:min_width$}",
+        "global", "balance <-1.0..1.0>"
     )
     .unwrap();
+    writeln!(help_str, "{:min_width$} {:min_width$}", "global", "loop-a").unwrap();
+    writeln!(help_str, "{:min_width$} {:min_width$}", "global", "loop-b").unwrap();
     writeln!(
         help_str,
         "{:min_width$} {:min_width$}",
-        "global", "quit-after=<u64>"
+        "global", "loop-clear"
     )
     .unwrap();
     writeln!(
         help_str,
         "{:min_width$} {:min_width$}",
-        "global", "view <player|chapters|help>"
+        "global", "repeat-chapter [on|off]"
     )
     .unwrap();
 
@@ -1,24 +1,60 @@
+use crate::config::NormalizationMode;
+use crate::timer::{
+    TimerAction, TimerRequest, parse_timer_action, parse_timer_clock, parse_timer_length,
+};
+
 #[derive(Debug, Clone)]
 pub enum TuiCommand {
     State(TuiState),
     Quit,
     Volume(i64),
     SetVolume(i64),
+    Speed(f64),
+    SetSpeed(f64),
     Seek(f64),
     SetPosition(f64),
+    UndoSeek,
+    RedoSeek,
+    BookmarkAdd(String),
+    BookmarkDelete(Option<usize>),
+    BookmarkGoto(usize),
+    ClipExport((f64, f64, Option<String>)),
+    BookmarkExport((usize, usize, Option<String>)),
     PlayPause,
     NextChapter,
     PrevChapter,
     EnterCommandMode(bool),
-    PauseAfter(u64),
-    QuitAfter(u64),
+    TimerAdd(TimerRequest),
+    TimerCancel(Option<String>),
     Scroll(i16),
+    Select,
+    SkipSilence(bool),
+    Normalization(NormalizationMode),
+    Compressor(bool),
+    Eq(String),
+    Mono(bool),
+    Balance(f64),
+    LoopA,
+    LoopB,
+    LoopClear,
+    RepeatChapter(Option<bool>),
+    QueueAdd(String),
+    QueueClear,
+    TranscriptSearch(String),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TuiState {
     Player,
     Chapters,
+    Library,
+    Recent,
+    Queue,
+    Timers,
+    Devices,
+    History,
+    Bookmarks,
+    Transcript,
     Help,
 }
 
@@ -32,21 +68,24 @@ fn seek(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
         let offset: f64 = arg.parse().ok()?;
         Some(TuiCommand::Seek(offset))
     } else {
-        if let Some(pos) = arg.parse().ok() {
-            Some(TuiCommand::SetPosition(pos))
-        } else if arg.chars().filter(|&c| c == ':').count() == 2 {
-            let (hh, mmss) = arg.split_once(':')?;
-            let (mm, ss) = mmss.split_once(':')?;
-
-            let hh: f64 = hh.parse().ok()?;
-            let mm: f64 = mm.parse().ok()?;
-            let ss: f64 = ss.parse().ok()?;
-            let pos = (hh * 60.0 * 60.0) + (mm * 60.0) + ss;
-
-            Some(TuiCommand::SetPosition(pos))
-        } else {
-            None
-        }
+        parse_position(arg).map(TuiCommand::SetPosition)
+    }
+}
+
+fn parse_position(arg: &str) -> Option<f64> {
+    if let Some(pos) = arg.parse().ok() {
+        Some(pos)
+    } else if arg.chars().filter(|&c| c == ':').count() == 2 {
+        let (hh, mmss) = arg.split_once(':')?;
+        let (mm, ss) = mmss.split_once(':')?;
+
+        let hh: f64 = hh.parse().ok()?;
+        let mm: f64 = mm.parse().ok()?;
+        let ss: f64 = ss.parse().ok()?;
+
+        Some((hh * 60.0 * 60.0) + (mm * 60.0) + ss)
+    } else {
+        None
     }
 }
 
@@ -63,6 +102,75 @@ fn vol(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
     }
 }
 
+fn speed(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    let arg = args.next()?;
+    let speed: f64 = arg.parse().ok().filter(|s: &f64| s.is_finite())?;
+    if arg.starts_with('-') || arg.starts_with('+') {
+        Some(TuiCommand::Speed(speed))
+    } else {
+        Some(TuiCommand::SetSpeed(speed))
+    }
+}
+
+fn undoseek(_: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    Some(TuiCommand::UndoSeek)
+}
+
+fn redoseek(_: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    Some(TuiCommand::RedoSeek)
+}
+
+fn history(_: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    Some(TuiCommand::State(TuiState::History))
+}
+
+fn bookmark(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    let note = args.collect::<Vec<&str>>().join(" ");
+    Some(TuiCommand::BookmarkAdd(note))
+}
+
+fn bookmarks(_: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    Some(TuiCommand::State(TuiState::Bookmarks))
+}
+
+// Bookmarks are numbered from 1 as in the bookmarks view.
+fn bookmark_number(arg: &str) -> Option<usize> {
+    arg.parse::<usize>().ok()?.checked_sub(1)
+}
+
+fn bookmarkdelete(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    match args.next() {
+        None => Some(TuiCommand::BookmarkDelete(None)),
+        Some(arg) => Some(TuiCommand::BookmarkDelete(Some(bookmark_number(arg)?))),
+    }
+}
+
+fn bookmarkgoto(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    Some(TuiCommand::BookmarkGoto(bookmark_number(args.next()?)?))
+}
+
+fn clipexport(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    let start = parse_position(args.next()?)?;
+    let end = parse_position(args.next()?)?;
+    let path = args.collect::<Vec<&str>>().join(" ");
+    Some(TuiCommand::ClipExport((
+        start,
+        end,
+        (!path.is_empty()).then_some(path),
+    )))
+}
+
+fn bookmarkexport(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    let from = bookmark_number(args.next()?)?;
+    let to = bookmark_number(args.next()?)?;
+    let path = args.collect::<Vec<&str>>().join(" ");
+    Some(TuiCommand::BookmarkExport((
+        from,
+        to,
+        (!path.is_empty()).then_some(path),
+    )))
+}
+
 fn playpause(_: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
     Some(TuiCommand::PlayPause)
 }
@@ -76,13 +184,46 @@ fn playprev(_: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
 }
 
 fn pauseafter(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
-    let time_min: u64 = args.next()?.parse().ok()?;
-    Some(TuiCommand::PauseAfter(time_min))
+    Some(TuiCommand::TimerAdd(TimerRequest {
+        name: "pause".to_string(),
+        action: TimerAction::Pause,
+        length: parse_timer_length(args.next()?)?,
+        clock: None,
+    }))
 }
 
 fn quitafter(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
-    let time_min: u64 = args.next()?.parse().ok()?;
-    Some(TuiCommand::QuitAfter(time_min))
+    Some(TuiCommand::TimerAdd(TimerRequest {
+        name: "quit".to_string(),
+        action: TimerAction::Quit,
+        length: parse_timer_length(args.next()?)?,
+        clock: None,
+    }))
+}
+
+fn timer(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    let command = match args.next()? {
+        "add" => {
+            let name = args.next()?.to_string();
+            let action = parse_timer_action(args.next()?)?;
+            let length = parse_timer_length(args.next()?)?;
+            let clock = match args.next() {
+                Some(clock) => Some(parse_timer_clock(clock)?),
+                None => None,
+            };
+            TuiCommand::TimerAdd(TimerRequest {
+                name,
+                action,
+                length,
+                clock,
+            })
+        }
+        "list" => TuiCommand::State(TuiState::Timers),
+        "cancel" => TuiCommand::TimerCancel(args.next().map(|s| s.to_string())),
+        _ => return None,
+    };
+
+    args.next().is_none().then_some(command)
 }
 
 fn view(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
@@ -90,11 +231,111 @@ fn view(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
     match arg {
         "player" => Some(TuiCommand::State(TuiState::Player)),
         "chapters" => Some(TuiCommand::State(TuiState::Chapters)),
+        "library" => Some(TuiCommand::State(TuiState::Library)),
+        "recent" => Some(TuiCommand::State(TuiState::Recent)),
+        "queue" => Some(TuiCommand::State(TuiState::Queue)),
+        "timers" => Some(TuiCommand::State(TuiState::Timers)),
+        "devices" => Some(TuiCommand::State(TuiState::Devices)),
+        "history" => Some(TuiCommand::State(TuiState::History)),
+        "bookmarks" => Some(TuiCommand::State(TuiState::Bookmarks)),
+        "transcript" => Some(TuiCommand::State(TuiState::Transcript)),
         "help" => Some(TuiCommand::State(TuiState::Help)),
         _ => None,
     }
 }
 
+fn skipsilence(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    match args.next()? {
+        "on" => Some(TuiCommand::SkipSilence(true)),
+        "off" => Some(TuiCommand::SkipSilence(false)),
+        _ => None,
+    }
+}
+
+fn normalize(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    match args.next()? {
+        "off" => Some(TuiCommand::Normalization(NormalizationMode::Off)),
+        "loudnorm" => Some(TuiCommand::Normalization(NormalizationMode::Loudnorm)),
+        "dynaudnorm" => Some(TuiCommand::Normalization(NormalizationMode::Dynaudnorm)),
+        _ => None,
+    }
+}
+
+fn compressor(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    match args.next()? {
+        "on" => Some(TuiCommand::Compressor(true)),
+        "off" => Some(TuiCommand::Compressor(false)),
+        _ => None,
+    }
+}
+
+fn eq(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    Some(TuiCommand::Eq(args.next()?.to_string()))
+}
+
+fn devices(_: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    Some(TuiCommand::State(TuiState::Devices))
+}
+
+fn mono(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    match args.next()? {
+        "on" => Some(TuiCommand::Mono(true)),
+        "off" => Some(TuiCommand::Mono(false)),
+        _ => None,
+    }
+}
+
+fn balance(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    let balance = args.next()?.parse::<f64>().ok()?;
+    (-1.0..=1.0)
+        .contains(&balance)
+        .then_some(TuiCommand::Balance(balance))
+}
+
+fn loopa(_: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    Some(TuiCommand::LoopA)
+}
+
+fn loopb(_: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    Some(TuiCommand::LoopB)
+}
+
+fn loopclear(_: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    Some(TuiCommand::LoopClear)
+}
+
+fn repeatchapter(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    match args.next() {
+        None => Some(TuiCommand::RepeatChapter(None)),
+        Some("on") => Some(TuiCommand::RepeatChapter(Some(true))),
+        Some("off") => Some(TuiCommand::RepeatChapter(Some(false))),
+        _ => None,
+    }
+}
+
+fn queue(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    let command = match args.next()? {
+        "add" => {
+            let path = args.collect::<Vec<&str>>().join(" ");
+            return (!path.is_empty()).then_some(TuiCommand::QueueAdd(path));
+        }
+        "list" => TuiCommand::State(TuiState::Queue),
+        "clear" => TuiCommand::QueueClear,
+        _ => return None,
+    };
+
+    args.next().is_none().then_some(command)
+}
+
+fn transcript(_: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    Some(TuiCommand::State(TuiState::Transcript))
+}
+
+fn transcriptsearch(args: &mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand> {
+    let text = args.collect::<Vec<&str>>().join(" ");
+    (!text.is_empty()).then_some(TuiCommand::TranscriptSearch(text))
+}
+
 type CmdFn = fn(&mut std::str::SplitWhitespace<'_>) -> Option<TuiCommand>;
 
 static COMMANDS: phf::Map<
@@ -105,37 +346,104 @@ static COMMANDS: phf::Map<
     "q" => quit as CmdFn,
     "vol" => vol as CmdFn,
     "seek" => seek as CmdFn,
+    "speed" => speed as CmdFn,
+    "undo-seek" => undoseek as CmdFn,
+    "redo-seek" => redoseek as CmdFn,
+    "history" => history as CmdFn,
+    "bookmark" => bookmark as CmdFn,
+    "bookmarks" => bookmarks as CmdFn,
+    "bookmark-delete" => bookmarkdelete as CmdFn,
+    "bookmark-goto" => bookmarkgoto as CmdFn,
+    "bookmark-export" => bookmarkexport as CmdFn,
+    "clip-export" => clipexport as CmdFn,
     "play-pause" => playpause as CmdFn,
     "play-next" => playnext as CmdFn,
     "play-prev" => playprev as CmdFn,
     "pause-after" => pauseafter as CmdFn,
     "quit-after" => quitafter as CmdFn,
     "view" => view as CmdFn,
+    "skip-silence" => skipsilence as CmdFn,
+    "normalize" => normalize as CmdFn,
+    "compressor" => compressor as CmdFn,
+    "eq" => eq as CmdFn,
+    "devices" => devices as CmdFn,
+    "mono" => mono as CmdFn,
+    "balance" => balance as CmdFn,
+    "loop-a" => loopa as CmdFn,
+    "loop-b" => loopb as CmdFn,
+    "loop-clear" => loopclear as CmdFn,
+    "repeat-chapter" => repeatchapter as CmdFn,
+    "queue" => queue as CmdFn,
+    "timer" => timer as CmdFn,
+    "transcript" => transcript as CmdFn,
+    "transcript-search" => transcriptsearch as CmdFn,
 };
 
+// Commands taking more than one argument, or free text that may contain spaces.
+static MULTI_ARG_COMMANDS: [&str; 6] = [
+    "queue",
+    "timer",
+    "bookmark",
+    "clip-export",
+    "bookmark-export",
+    "transcript-search",
+];
+
 pub fn map_str_to_tuicommand(str: &str) -> Option<TuiCommand> {
-    if str.split_whitespace().count() > 2 {
+    let mut tokens = str.split_whitespace();
+    let command_str = tokens.next()?;
+    if str.split_whitespace().count() > 2 && !MULTI_ARG_COMMANDS.contains(&command_str) {
         return None;
     }
 
-    let mut tokens = str.split_whitespace();
-    let command_str = tokens.next()?;
+    let command = COMMANDS.get(command_str).map(|f| f(&mut tokens))??;
+    // Paths are taken verbatim, splitting on whitespace would collapse runs of spaces.
+    let command = match command {
+        TuiCommand::QueueAdd(_) => TuiCommand::QueueAdd(text_after(str, 2).to_string()),
+        TuiCommand::ClipExport((start, end, Some(_))) => {
+            TuiCommand::ClipExport((start, end, Some(text_after(str, 3).to_string())))
+        }
+        TuiCommand::BookmarkExport((from, to, Some(_))) => {
+            TuiCommand::BookmarkExport((from, to, Some(text_after(str, 3).to_string())))
+        }
+        command => command,
+    };
+
+    Some(command)
+}
 
-    COMMANDS.get(command_str).map(|f| f(&mut tokens))?
+// The rest of the command text after its first `n` words.
+fn text_after(str: &str, n: usize) -> &str {
+    let mut rest = str.trim_start();
+    for _ in 0..n {
+        rest = rest
+            .trim_start_matches(|c: char| !c.is_whitespace())
+            .trim_start();
+    }
+
+    rest.trim_end()
 }
 
-pub fn generate_completion_suggestions(command_text: &str) -> Vec<&'static str> {
-    let commands_names = COMMANDS.keys();
+pub fn generate_completion_suggestions(command_text: &str, eq_presets: &[String]) -> Vec<String> {
+    // Arguments are only completed once the command name is typed out.
+    let candidates: Vec<String> = if command_text.starts_with("eq ") {
+        eq_presets
+            .iter()
+            .map(|preset| format!("eq {preset}"))
+            .collect()
+    } else {
+        COMMANDS.keys().map(|name| name.to_string()).collect()
+    };
 
     let mut suggestions = Vec::new();
-    for name in commands_names {
-        if let Some(dist) = calculate_insertion_distance(command_text, name) {
+    for name in candidates {
+        if let Some(dist) = calculate_insertion_distance(command_text, &name) {
             suggestions.push((name, dist));
         }
     }
-    suggestions.sort_by_key(|&(_, dist)| dist);
+    suggestions.sort_by_key(|(_, dist)| *dist);
 
-    suggestions.iter().map(|&(name, _)| *name).collect()
+    suggestions.into_iter().map(|(name, _)| name).collect()
 }
 
 fn calculate_insertion_distance(from: &str, to: &str) -> Option<u8> {
@@ -146,3 +454,28 @@ fn calculate_insertion_distance(from: &str, to: &str) -> Option<u8> {
 
     Some(insertions.count().try_into().ok()?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queue_add_keeps_spaces() {
+        let command = map_str_to_tuicommand("queue add /books/A  B\tC ");
+        assert!(matches!(command, Some(TuiCommand::QueueAdd(path)) if path == "/books/A  B\tC"));
+    }
+
+    #[test]
+    fn clip_export_keeps_spaces() {
+        let command = map_str_to_tuicommand("clip-export 10 00:01:00 clips/A  B.mp3");
+        assert!(matches!(
+            command,
+            Some(TuiCommand::ClipExport((10.0, 60.0, Some(path)))) if path == "clips/A  B.mp3"
+        ));
+        let command = map_str_to_tuicommand("clip-export 10 20");
+        assert!(matches!(
+            command,
+            Some(TuiCommand::ClipExport((10.0, 20.0, None)))
+        ));
+    }
+}
@@ -16,6 +16,37 @@ impl Keybindings {
                 KeyEvent::new(KeyCode::Char('2'), KeyModifiers::NONE),
                 (TuiCommand::State(TuiState::Chapters), Some("view chapters")),
             ),
+            (
+                KeyEvent::new(KeyCode::Char('3'), KeyModifiers::NONE),
+                (TuiCommand::State(TuiState::Library), Some("view library")),
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('4'), KeyModifiers::NONE),
+                (TuiCommand::State(TuiState::Recent), Some("view recent")),
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('5'), KeyModifiers::NONE),
+                (TuiCommand::State(TuiState::Queue), Some("view queue")),
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('6'), KeyModifiers::NONE),
+                (TuiCommand::State(TuiState::Timers), Some("view timers")),
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('7'), KeyModifiers::NONE),
+                (TuiCommand::State(TuiState::Devices), Some("view devices")),
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('8'), KeyModifiers::NONE),
+                (TuiCommand::State(TuiState::History), Some("view history")),
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('9'), KeyModifiers::NONE),
+                (
+                    TuiCommand::State(TuiState::Bookmarks),
+                    Some("view bookmarks"),
+                ),
+            ),
             (
                 KeyEvent::new(KeyCode::Char('0'), KeyModifiers::NONE),
                 (TuiCommand::State(TuiState::Help), Some("view help")),
@@ -40,6 +71,34 @@ impl Keybindings {
                 KeyEvent::new(KeyCode::Char(']'), KeyModifiers::NONE),
                 (TuiCommand::Volume(10), Some("vol +10")),
             ),
+            (
+                KeyEvent::new(KeyCode::Char('<'), KeyModifiers::NONE),
+                (TuiCommand::Speed(-0.1), Some("speed -0.1")),
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('>'), KeyModifiers::NONE),
+                (TuiCommand::Speed(0.1), Some("speed +0.1")),
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('='), KeyModifiers::NONE),
+                (TuiCommand::SetSpeed(1.0), Some("speed 1")),
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('('), KeyModifiers::NONE),
+                (TuiCommand::LoopA, Some("loop-a")),
+            ),
+            (
+                KeyEvent::new(KeyCode::Char(')'), KeyModifiers::NONE),
+                (TuiCommand::LoopB, Some("loop-b")),
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE),
+                (TuiCommand::LoopClear, Some("loop-clear")),
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE),
+                (TuiCommand::RepeatChapter(None), Some("repeat-chapter")),
+            ),
             (
                 KeyEvent::new(KeyCode::Left, KeyModifiers::NONE),
                 (TuiCommand::Seek(-10.0), Some("seek -10")),
@@ -56,6 +115,18 @@ impl Keybindings {
                 KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT),
                 (TuiCommand::Seek(60.0), Some("seek -60")),
             ),
+            (
+                KeyEvent::new(KeyCode::Char('m'), KeyModifiers::NONE),
+                (TuiCommand::BookmarkAdd(String::new()), Some("bookmark")),
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE),
+                (TuiCommand::UndoSeek, Some("undo-seek")),
+            ),
+            (
+                KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL),
+                (TuiCommand::RedoSeek, Some("redo-seek")),
+            ),
             (
                 KeyEvent::new(KeyCode::Char('z'), KeyModifiers::NONE),
                 (TuiCommand::PrevChapter, Some("play-prev")),
@@ -76,6 +147,10 @@ impl Keybindings {
                 KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE),
                 (TuiCommand::Scroll(-1), Some("scroll -1")),
             ),
+            (
+                KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+                (TuiCommand::Select, Some("select")),
+            ),
             (
                 KeyEvent::new(KeyCode::Char(':'), KeyModifiers::NONE),
                 (TuiCommand::EnterCommandMode(true), None),